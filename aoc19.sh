@@ -2,11 +2,11 @@ set -e
 RUSTFLAGS=
 
 DAY=$1
-QUESTION=$2
-INPUTFILE=./inputs/$DAY.txt
 
-if [ ! -f $INPUTFILE ]; then
-    curl "https://adventofcode.com/2019/day/$DAY/input" -H "cookie: $(cat cookie.txt)" --compressed > $INPUTFILE
-fi
+# download the input if it's missing
+case $DAY in
+    ''|*[!0-9]*) ;;
+    *) cargo run -q -- inputs fetch $DAY ;;
+esac
 
 cargo run -- $@
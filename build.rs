@@ -0,0 +1,147 @@
+//! Generates the table of solutions by scanning `src/` for `dayN` modules, so
+//! that registering a new day is as simple as creating it's file.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A `dayN` module found in `src/`.
+struct Day {
+    /// Path to the module's source (`dayN.rs` or `dayN/mod.rs`).
+    path: PathBuf,
+    source: String,
+}
+
+impl Day {
+    fn has_fn(&self, name: &str) -> bool {
+        let decl = format!("pub fn {}(", name);
+        self.source.lines().any(|ln| ln.starts_with(&decl))
+    }
+
+    fn has_const(&self, name: &str) -> bool {
+        let decl = format!("pub const {}:", name);
+        self.source.lines().any(|ln| ln.starts_with(&decl))
+    }
+
+    /// Make sure every file in the module's directory is actually declared as
+    /// a submodule (e.g: `day7/q2_threaded.rs`, which is gated behind the
+    /// "extras" feature).
+    fn check_submodules(&self, dir: &Path) {
+        if !dir.is_dir() {
+            return;
+        }
+
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let name = match path.file_stem().and_then(|s| s.to_str()) {
+                Some(name) if path.extension().map(|e| e == "rs").unwrap_or(false) => name,
+                _ => continue,
+            };
+            if name == "mod" {
+                continue;
+            }
+
+            let decl = format!("mod {};", name);
+            if !self.source.lines().any(|ln| ln.trim().ends_with(&decl)) {
+                panic!(
+                    "{} is never declared as a module in {}",
+                    path.display(),
+                    self.path.display()
+                );
+            }
+        }
+    }
+}
+
+/// Parse a `dayN` module name out of a file / directory name. The generated
+/// `mod` declarations have to match the file names, so non-canonical numbers
+/// (e.g: `day01.rs`) are skipped with a warning.
+fn day_num(file_name: &str) -> Option<u32> {
+    let name = file_name.strip_suffix(".rs").unwrap_or(file_name);
+    let digits = name.strip_prefix("day")?;
+    let num = digits.parse::<u32>().ok()?;
+    if num.to_string() != digits {
+        println!(
+            "cargo:warning=skipping src/{}: rename it to day{}",
+            file_name, num
+        );
+        return None;
+    }
+    Some(num)
+}
+
+fn main() {
+    let src = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("src");
+    println!("cargo:rerun-if-changed={}", src.display());
+
+    let mut days = BTreeMap::new();
+    for entry in fs::read_dir(&src).unwrap() {
+        let entry = entry.unwrap();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let num = match day_num(&name) {
+            Some(num) => num,
+            None => continue,
+        };
+
+        let path = match entry.path() {
+            dir if dir.is_dir() => dir.join("mod.rs"),
+            file if name.ends_with(".rs") => file,
+            _ => continue,
+        };
+
+        // `dayN/` directories hold the submodules of `dayN.rs`
+        if !path.exists() {
+            continue;
+        }
+
+        let source = fs::read_to_string(&path).unwrap();
+        days.insert(num, Day { path, source });
+    }
+
+    let mut mods = String::new();
+    let mut table = String::new();
+    for (num, day) in &days {
+        day.check_submodules(&src.join(format!("day{}", num)));
+
+        mods += &format!("    pub mod day{};\n", num);
+
+        for (q, opts, slow) in &[("q1", "Q1_OPTS", "Q1_SLOW"), ("q2", "Q2_OPTS", "Q2_SLOW")] {
+            if !day.has_fn(q) {
+                continue;
+            }
+
+            let opts = match day.has_const(opts) {
+                true => format!("days::day{}::{}", num, opts),
+                false => "&[]".to_string(),
+            };
+            let slow = match day.has_const(slow) {
+                true => format!("Some(days::day{}::{})", num, slow),
+                false => "None".to_string(),
+            };
+            table += &format!(
+                "        solution!(day{}, {}, {}, {}),\n",
+                num, q, opts, slow
+            );
+        }
+    }
+
+    let generated = format!(
+        "// @generated by build.rs
+
+#[path = {:?}]
+mod days {{
+{}}}
+
+/// Returns every day's questions, in order
+fn solutions() -> Vec<harness::Solution> {{
+    vec![
+{}    ]
+}}
+",
+        src, mods, table
+    );
+
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("days.rs");
+    fs::write(out, generated).unwrap();
+}
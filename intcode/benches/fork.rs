@@ -0,0 +1,93 @@
+//! Compares the cost of forking an Intcode machine against cloning the flat
+//! `Vec` + `HashMap` memory layout the interpreter used prior to copy-on-write
+//! pages.
+//!
+//! Run with `cargo bench -p intcode`.
+
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use intcode::Intcode;
+
+/// Roughly the size of a typical puzzle program
+const PROGRAM_LEN: usize = 2048;
+/// Number of spilled-over high memory addresses
+const HI_MEM_LEN: usize = 512;
+/// Number of words written to the fork after it's created
+const DIRTY_WORDS: usize = 8;
+
+const ITERS: u32 = 10_000;
+
+/// The pre-copy-on-write memory layout
+#[derive(Clone)]
+struct FlatMem {
+    _orig_mem: Vec<isize>,
+    lo_mem: Vec<isize>,
+    hi_mem: HashMap<usize, isize>,
+}
+
+impl FlatMem {
+    fn write(&mut self, addr: usize, val: isize) {
+        match self.lo_mem.get_mut(addr) {
+            Some(v) => *v = val,
+            None => {
+                self.hi_mem.insert(addr, val);
+            }
+        }
+    }
+}
+
+fn time(name: &str, mut f: impl FnMut()) {
+    // warmup
+    for _ in 0..ITERS / 10 {
+        f();
+    }
+
+    let start = Instant::now();
+    for _ in 0..ITERS {
+        f();
+    }
+    let per_iter: Duration = start.elapsed() / ITERS;
+    println!("{:<28} {:>10.2?}", name, per_iter);
+}
+
+fn main() {
+    let program = (0..PROGRAM_LEN)
+        .map(|i| (i % 100).to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let mut intcode = Intcode::new(&program).unwrap();
+    let mut flat = FlatMem {
+        _orig_mem: intcode.mem_ref().orig_mem().to_vec(),
+        lo_mem: intcode.mem_ref().orig_mem().to_vec(),
+        hi_mem: HashMap::new(),
+    };
+    for i in 0..HI_MEM_LEN {
+        intcode.mem().write(PROGRAM_LEN * 4 + i * 7, i as isize);
+        flat.write(PROGRAM_LEN * 4 + i * 7, i as isize);
+    }
+
+    time("flat: clone", || {
+        black_box(flat.clone());
+    });
+    time("cow: clone", || {
+        black_box(intcode.clone());
+    });
+
+    time("flat: clone + dirty writes", || {
+        let mut fork = flat.clone();
+        for i in 0..DIRTY_WORDS {
+            fork.write(i * 300, 1);
+        }
+        black_box(fork);
+    });
+    time("cow: clone + dirty writes", || {
+        let mut fork = intcode.clone();
+        for i in 0..DIRTY_WORDS {
+            fork.mem().write(i * 300, 1);
+        }
+        black_box(fork);
+    });
+}
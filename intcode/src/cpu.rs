@@ -4,6 +4,7 @@ use std::result::Result as StdResult;
 
 use super::error::{Error, Result};
 use super::mem::Mem;
+use super::stats::Stats;
 
 /// Macro to reuse step implementation across `step` and `step_async` functions.
 /// The two are identical, except for their behavior when performing I/O.
@@ -14,7 +15,8 @@ macro_rules! impl_step {
         impl Puti($src:ident) => $puti:expr,
     ) => {{
         use Instruction::*;
-        match $self.fetch_decode_instr()? {
+        let instr = $self.fetch_decode_instr()?;
+        match instr {
             Add_(a, b, dst) => $self.mem.write(dst, a + b),
             Mul_(a, b, dst) => $self.mem.write(dst, a * b),
             Geti($dst) => $geti,
@@ -32,10 +34,13 @@ macro_rules! impl_step {
             Cmp_(a, b, dst) => $self.mem.write(dst, (a < b) as isize),
             Eq__(a, b, dst) => $self.mem.write(dst, (a == b) as isize),
             Setb(b) => $self.base += b,
-            Halt => return Ok(false),
+            Halt => {}
         }
 
-        Ok(true)
+        // only record instructions which executed successfully (e.g: input
+        // isn't counted unless it was actually consumed)
+        $self.stats.record(&instr);
+        Ok(!matches!(instr, Halt))
     }};
 }
 
@@ -46,6 +51,7 @@ pub struct Intcode {
     instr: usize,
     pc: usize,
     base: isize,
+    stats: Stats,
 }
 
 impl Intcode {
@@ -57,15 +63,18 @@ impl Intcode {
             instr: 0,
             pc: 0,
             base: 0,
+            stats: Stats::default(),
         })
     }
 
     /// Reset the intcode machine to it's initial state
     pub fn reset(&mut self) {
         self.mem.reset();
+        self.mem.reset_peak();
         self.instr = 0;
         self.pc = 0;
         self.base = 0;
+        self.stats = Stats::default();
     }
 
     /// Return a mutable reference to the intcode machine's memory
@@ -73,6 +82,45 @@ impl Intcode {
         &mut self.mem
     }
 
+    /// Return a reference to the intcode machine's memory, for inspection
+    pub fn mem_ref(&self) -> &Mem {
+        &self.mem
+    }
+
+    /// Returns the execution statistics accumulated since the machine was
+    /// created (or last reset)
+    pub fn stats(&self) -> Stats {
+        let mut stats = self.stats.clone();
+        stats.peak_hi_mem = self.mem.peak_hi_mem_len();
+        stats.hi_mem = self.mem.hi_mem_len();
+        stats
+    }
+
+    /// Returns the current program counter
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    /// Set the program counter
+    pub fn set_pc(&mut self, pc: usize) {
+        self.pc = pc;
+    }
+
+    /// Returns the current relative base
+    pub fn base(&self) -> isize {
+        self.base
+    }
+
+    /// Set the relative base
+    pub fn set_base(&mut self, base: isize) {
+        self.base = base;
+    }
+
+    /// Returns `true` if the next instruction to be executed reads input.
+    pub fn needs_input(&self) -> bool {
+        self.mem.peek(self.pc) % 100 == 3
+    }
+
     /// Return the next argument, taking into account it's addressing mode, and
     /// incrementing `self.pc` by 1.
     fn fetch_arg(&mut self) -> Result<usize> {
@@ -185,7 +233,7 @@ impl IsizeIntcodeExt for isize {
         if self < 0 {
             Err(Error::NegativeAddr)
         } else {
-            Ok(self.abs() as usize)
+            Ok(self.unsigned_abs())
         }
     }
 
@@ -193,7 +241,7 @@ impl IsizeIntcodeExt for isize {
         if self < 0 {
             Err(Error::NegativeInstr)
         } else {
-            Ok(self.abs() as usize)
+            Ok(self.unsigned_abs())
         }
     }
 }
@@ -5,8 +5,11 @@ pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
 pub enum Error {
+    #[cfg(feature = "gdb")]
+    ConnectionError(std::io::Error),
     InputError(Box<dyn StdError>),
     OutputError(Box<dyn StdError>),
+    InterpretError(Box<dyn StdError>),
     InvalidAddrMode(usize),
     InvalidOpcode(usize),
     NegativeAddr,
@@ -20,8 +23,11 @@ impl Display for Error {
         match self {
             InvalidAddrMode(v) => write!(f, "Encountered unknown addressing mode: {}", v),
             InvalidOpcode(v) => write!(f, "Encountered unknown opcode: {}", v),
+            #[cfg(feature = "gdb")]
+            ConnectionError(e) => write!(f, "Debugger connection error: {}", e),
             InputError(e) => write!(f, "Could not read input: {}", e),
             OutputError(e) => write!(f, "Could not read output: {}", e),
+            InterpretError(e) => write!(f, "Could not interpret output: {}", e),
             NegativeAddr => write!(f, "Cannot address negative address"),
             NegativeInstr => write!(f, "Cannot execute negative instruction"),
             ParseMem => write!(f, "Failed to parse initial memory string"),
@@ -0,0 +1,554 @@
+//! A GDB Remote Serial Protocol stub, for attaching a standard debugger
+//! frontend to a running intcode program.
+//!
+//! The machine is exposed to the debugger as follows:
+//!
+//! - Two 64-bit registers: `pc` (register 0) and `base` (register 1)
+//! - Memory, with each intcode word presented as 8 little-endian bytes (i.e:
+//!   word `n` lives at byte address `n * 8`)
+//! - Software breakpoints (`Z0`/`z0`), placed on word-aligned addresses
+//! - A target description (`target.xml`) naming the registers, so stock gdb
+//!   can interpret register packets
+//! - Errors which stop the machine are printed to the debugger's console,
+//!   followed by a `SIGILL` stop reply
+//!
+//! The stub can be served over any `Read + Write` connection, with helpers
+//! for accepting a single TCP connection ([`listen`]) or using stdio
+//! ([`Stdio`]).
+//!
+//! Asynchronous interrupts (i.e: Ctrl-C while the target is running) are not
+//! supported, so make sure to set breakpoints before continuing.
+
+use std::collections::HashSet;
+use std::error::Error as StdError;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::result::Result as StdResult;
+
+use crate::{Error, Intcode, Result};
+
+/// Number of bytes used to represent a single intcode word.
+pub const WORD_SIZE: usize = 8;
+
+/// Maximum packet size advertised to the debugger.
+const PACKET_SIZE: usize = 0x1000;
+
+/// Describes the machine's registers to the debugger.
+const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.aoc19.intcode">
+    <reg name="pc" bitsize="64" type="code_ptr" regnum="0"/>
+    <reg name="base" bitsize="64" type="int64" regnum="1"/>
+  </feature>
+</target>
+"#;
+
+/// Why the machine stopped executing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stop {
+    /// Stopped after a single step, or at a breakpoint.
+    Trap,
+    /// Encountered an error while executing an instruction.
+    Fault,
+    /// Executed a halt instruction.
+    Exited,
+}
+
+impl Stop {
+    fn reply(self) -> &'static str {
+        match self {
+            Stop::Trap => "S05",  // SIGTRAP
+            Stop::Fault => "S04", // SIGILL
+            Stop::Exited => "W00",
+        }
+    }
+}
+
+/// A GDB Remote Serial Protocol stub, served over a connection `C`.
+pub struct GdbStub<C> {
+    conn: C,
+    breakpoints: HashSet<usize>,
+    no_ack: bool,
+}
+
+/// A connection which reads from stdin, and writes to stdout.
+pub struct Stdio;
+
+impl Read for Stdio {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        io::stdin().read(buf)
+    }
+}
+
+impl Write for Stdio {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::stdout().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
+
+/// Block until a debugger connects to `addr`, returning a stub which serves
+/// the connection.
+pub fn listen(addr: impl ToSocketAddrs) -> Result<GdbStub<TcpStream>> {
+    let listener = TcpListener::bind(addr).map_err(Error::ConnectionError)?;
+    let (stream, _) = listener.accept().map_err(Error::ConnectionError)?;
+    // packets are small and latency sensitive
+    stream.set_nodelay(true).map_err(Error::ConnectionError)?;
+    Ok(GdbStub::new(stream))
+}
+
+impl<C: Read + Write> GdbStub<C> {
+    /// Create a new stub which serves the debugger on the other end of
+    /// `conn`.
+    pub fn new(conn: C) -> GdbStub<C> {
+        GdbStub {
+            conn,
+            breakpoints: HashSet::new(),
+            no_ack: false,
+        }
+    }
+
+    /// Serve the debugger until it detaches, kills the target, or closes the
+    /// connection, using custom input/output functions whenever the machine
+    /// is resumed.
+    pub fn run(
+        &mut self,
+        intcode: &mut Intcode,
+        mut input_fn: impl FnMut() -> StdResult<isize, Box<dyn StdError>>,
+        mut output_fn: impl FnMut(isize) -> StdResult<(), Box<dyn StdError>>,
+    ) -> Result<()> {
+        let mut last_stop = Stop::Trap;
+
+        while let Some(packet) = self.recv_packet()? {
+            let packet = String::from_utf8_lossy(&packet);
+            let cmd = packet.get(..1).unwrap_or("");
+            let args = packet.get(1..).unwrap_or("");
+
+            let res = match cmd {
+                "?" => last_stop.reply().to_string(),
+                "q" if args.starts_with("Supported") => format!(
+                    "PacketSize={:x};QStartNoAckMode+;qXfer:features:read+",
+                    PACKET_SIZE
+                ),
+                "q" if args.starts_with("Xfer:features:read:") => {
+                    let args = &args["Xfer:features:read:".len()..];
+                    match args.split_once(':') {
+                        Some(("target.xml", range)) => match parse_addr_len(range) {
+                            Some((offset, len)) => xfer_chunk(TARGET_XML, offset, len),
+                            None => "E01".to_string(),
+                        },
+                        _ => "E00".to_string(),
+                    }
+                }
+                "q" if args.starts_with("Attached") => "1".to_string(),
+                "Q" if args == "StartNoAckMode" => {
+                    self.send_packet("OK")?;
+                    self.no_ack = true;
+                    continue;
+                }
+                "g" => {
+                    let pc = intcode.pc() as isize;
+                    format!("{}{}", to_hex(pc), to_hex(intcode.base()))
+                }
+                "G" => match (args.get(..16), args.get(16..32)) {
+                    (Some(pc), Some(base)) => match (from_hex(pc), from_hex(base)) {
+                        (Some(pc), Some(base)) if pc >= 0 => {
+                            intcode.set_pc(pc as usize);
+                            intcode.set_base(base);
+                            "OK".to_string()
+                        }
+                        _ => "E01".to_string(),
+                    },
+                    _ => "E01".to_string(),
+                },
+                "p" => match args {
+                    "0" => to_hex(intcode.pc() as isize),
+                    "1" => to_hex(intcode.base()),
+                    _ => "E01".to_string(),
+                },
+                "P" => {
+                    let mut args = args.splitn(2, '=');
+                    match (args.next(), args.next().and_then(from_hex)) {
+                        (Some("0"), Some(pc)) if pc >= 0 => {
+                            intcode.set_pc(pc as usize);
+                            "OK".to_string()
+                        }
+                        (Some("1"), Some(base)) => {
+                            intcode.set_base(base);
+                            "OK".to_string()
+                        }
+                        _ => "E01".to_string(),
+                    }
+                }
+                "m" => match parse_mem_range(args) {
+                    Some((addr, len)) => (addr..addr + len)
+                        .map(|b| {
+                            let word = intcode.mem_ref().peek(b / WORD_SIZE);
+                            format!("{:02x}", word.to_le_bytes()[b % WORD_SIZE])
+                        })
+                        .collect(),
+                    None => "E01".to_string(),
+                },
+                "M" => {
+                    let mut args = args.splitn(2, ':');
+                    let range = args.next().and_then(parse_mem_range);
+                    let data = args.next().and_then(parse_bytes);
+                    match (range, data) {
+                        (Some((addr, len)), Some(data)) if data.len() == len => {
+                            for (b, byte) in (addr..).zip(data) {
+                                let mem = intcode.mem();
+                                let mut word = mem.peek(b / WORD_SIZE).to_le_bytes();
+                                word[b % WORD_SIZE] = byte;
+                                mem.write(b / WORD_SIZE, isize::from_le_bytes(word));
+                            }
+                            "OK".to_string()
+                        }
+                        _ => "E01".to_string(),
+                    }
+                }
+                "Z" | "z" => {
+                    let mut args = args.split(',');
+                    let kind = args.next();
+                    let addr = args.next().and_then(|a| usize::from_str_radix(a, 16).ok());
+                    match (kind, addr) {
+                        (Some("0"), Some(addr)) if addr.is_multiple_of(WORD_SIZE) => {
+                            if cmd == "Z" {
+                                self.breakpoints.insert(addr / WORD_SIZE);
+                            } else {
+                                self.breakpoints.remove(&(addr / WORD_SIZE));
+                            }
+                            "OK".to_string()
+                        }
+                        (Some("0"), Some(_)) => "E01".to_string(),
+                        // only software breakpoints are supported
+                        _ => String::new(),
+                    }
+                }
+                "c" | "s" => {
+                    if last_stop != Stop::Exited {
+                        let stop = self.resume(intcode, cmd == "s", &mut input_fn, &mut output_fn);
+                        last_stop = match stop {
+                            Ok(stop) => stop,
+                            Err(e) => {
+                                // let the user know why the machine stopped
+                                let msg = format!("intcode fault: {}\n", e);
+                                self.send_packet(&format!("O{}", hex(msg.as_bytes())))?;
+                                Stop::Fault
+                            }
+                        };
+                    }
+                    last_stop.reply().to_string()
+                }
+                "D" => {
+                    self.send_packet("OK")?;
+                    return Ok(());
+                }
+                "k" => return Ok(()),
+                // unsupported packet
+                _ => String::new(),
+            };
+
+            self.send_packet(&res)?;
+        }
+
+        Ok(())
+    }
+
+    /// Resume execution until the next breakpoint (or for a single step).
+    /// Returns the error if the machine faulted.
+    fn resume(
+        &self,
+        intcode: &mut Intcode,
+        single_step: bool,
+        input_fn: &mut impl FnMut() -> StdResult<isize, Box<dyn StdError>>,
+        output_fn: &mut impl FnMut(isize) -> StdResult<(), Box<dyn StdError>>,
+    ) -> Result<Stop> {
+        loop {
+            if !intcode.step(&mut *input_fn, &mut *output_fn)? {
+                return Ok(Stop::Exited);
+            }
+
+            if single_step || self.breakpoints.contains(&intcode.pc()) {
+                return Ok(Stop::Trap);
+            }
+        }
+    }
+
+    fn read_byte(&mut self) -> Result<Option<u8>> {
+        let mut buf = [0];
+        match self.conn.read(&mut buf).map_err(Error::ConnectionError)? {
+            0 => Ok(None),
+            _ => Ok(Some(buf[0])),
+        }
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+        (self.conn.write_all(buf))
+            .and_then(|_| self.conn.flush())
+            .map_err(Error::ConnectionError)
+    }
+
+    /// Receive the next packet's data, acknowledging it if required. Returns
+    /// None if the connection was closed.
+    fn recv_packet(&mut self) -> Result<Option<Vec<u8>>> {
+        loop {
+            // skip acks (and anything else) until the start of a packet
+            loop {
+                match self.read_byte()? {
+                    None => return Ok(None),
+                    Some(b'$') => break,
+                    Some(_) => {}
+                }
+            }
+
+            let mut data = Vec::new();
+            loop {
+                match self.read_byte()? {
+                    None => return Ok(None),
+                    Some(b'#') => break,
+                    Some(b) => data.push(b),
+                }
+            }
+
+            let mut checksum = [0; 2];
+            for b in checksum.iter_mut() {
+                match self.read_byte()? {
+                    None => return Ok(None),
+                    Some(c) => *b = c,
+                }
+            }
+
+            let expected = std::str::from_utf8(&checksum)
+                .ok()
+                .and_then(|c| u8::from_str_radix(c, 16).ok());
+
+            if self.no_ack {
+                return Ok(Some(data));
+            }
+
+            if expected == Some(checksum_of(&data)) {
+                self.write_all(b"+")?;
+                return Ok(Some(data));
+            }
+
+            self.write_all(b"-")?;
+        }
+    }
+
+    /// Send a packet, retransmitting it until it's acknowledged (if
+    /// required).
+    fn send_packet(&mut self, data: &str) -> Result<()> {
+        let packet = format!("${}#{:02x}", data, checksum_of(data.as_bytes()));
+        loop {
+            self.write_all(packet.as_bytes())?;
+
+            if self.no_ack {
+                return Ok(());
+            }
+
+            match self.read_byte()? {
+                Some(b'-') => continue,
+                _ => return Ok(()),
+            }
+        }
+    }
+}
+
+fn checksum_of(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |a, b| a.wrapping_add(*b))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Encode a word as little-endian hex
+fn to_hex(val: isize) -> String {
+    hex(&val.to_le_bytes())
+}
+
+/// Decode a word from little-endian hex
+fn from_hex(s: &str) -> Option<isize> {
+    let bytes = parse_bytes(s)?;
+    let mut word = [0; WORD_SIZE];
+    if bytes.len() != WORD_SIZE {
+        return None;
+    }
+    word.copy_from_slice(&bytes);
+    Some(isize::from_le_bytes(word))
+}
+
+fn parse_bytes(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Parse an `addr,length` pair
+fn parse_addr_len(s: &str) -> Option<(usize, usize)> {
+    let mut s = s.splitn(2, ',');
+    let addr = usize::from_str_radix(s.next()?, 16).ok()?;
+    let len = usize::from_str_radix(s.next()?, 16).ok()?;
+    Some((addr, len))
+}
+
+/// Parse an `addr,length` pair describing a range of target memory, which
+/// must not overflow, and must fit in a single reply packet (at two hex digits
+/// per byte).
+fn parse_mem_range(s: &str) -> Option<(usize, usize)> {
+    let (addr, len) = parse_addr_len(s)?;
+    addr.checked_add(len)?;
+    if len > PACKET_SIZE / 2 {
+        return None;
+    }
+    Some((addr, len))
+}
+
+/// Reply to a `qXfer` read of `len` bytes of `doc`, starting at `offset`.
+fn xfer_chunk(doc: &str, offset: usize, len: usize) -> String {
+    // leave room for the `m` / `l` prefix
+    let len = len.min(PACKET_SIZE - 1);
+    let doc = doc.as_bytes();
+    let start = offset.min(doc.len());
+    let end = start.saturating_add(len).min(doc.len());
+    let chunk = String::from_utf8_lossy(&doc[start..end]);
+    match end == doc.len() {
+        true => format!("l{}", chunk),
+        false => format!("m{}", chunk),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc;
+    use std::thread;
+
+    struct Client {
+        conn: TcpStream,
+    }
+
+    impl Client {
+        fn send(&mut self, data: &str) -> String {
+            let packet = format!("${}#{:02x}", data, checksum_of(data.as_bytes()));
+            self.conn.write_all(packet.as_bytes()).unwrap();
+
+            let mut buf = [0];
+            self.conn.read_exact(&mut buf).unwrap();
+            assert_eq!(buf[0], b'+');
+
+            self.recv()
+        }
+
+        fn recv(&mut self) -> String {
+            let mut buf = [0];
+            let mut res = Vec::new();
+            self.conn.read_exact(&mut buf).unwrap();
+            assert_eq!(buf[0], b'$');
+            loop {
+                self.conn.read_exact(&mut buf).unwrap();
+                if buf[0] == b'#' {
+                    break;
+                }
+                res.push(buf[0]);
+            }
+            let mut checksum = [0; 2];
+            self.conn.read_exact(&mut checksum).unwrap();
+            self.conn.write_all(b"+").unwrap();
+
+            String::from_utf8(res).unwrap()
+        }
+    }
+
+    /// Serve `program` on a background thread, returning a connected client,
+    /// the server thread, and a channel of the program's outputs.
+    fn serve(program: &'static str) -> (Client, thread::JoinHandle<()>, mpsc::Receiver<isize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (tx, rx) = mpsc::channel();
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            stream.set_nodelay(true).unwrap();
+            let mut intcode = Intcode::new(program).unwrap();
+            GdbStub::new(stream)
+                .run(&mut intcode, || Ok(5), |o| Ok(tx.send(o)?))
+                .unwrap();
+        });
+
+        let conn = TcpStream::connect(addr).unwrap();
+        conn.set_nodelay(true).unwrap();
+        (Client { conn }, server, rx)
+    }
+
+    #[test]
+    fn scripted_session() {
+        // reads an input, doubles it, outputs it, and halts
+        let (mut gdb, server, rx) = serve("3,9,1,9,9,10,4,10,99,0,0");
+
+        assert!(gdb.send("qSupported").starts_with("PacketSize="));
+        assert_eq!(gdb.send("?"), "S05");
+
+        // break just before the output instruction (word 6)
+        assert_eq!(gdb.send("Z0,30,8"), "OK");
+        assert_eq!(gdb.send("c"), "S05");
+        assert_eq!(gdb.send("g"), "06000000000000000000000000000000");
+        assert_eq!(gdb.send("p0"), "0600000000000000");
+        assert_eq!(gdb.send("m48,8"), "0500000000000000");
+        assert_eq!(gdb.send("m50,8"), "0a00000000000000");
+
+        // patch the result before it's output
+        assert_eq!(gdb.send("M50,8:0b00000000000000"), "OK");
+        assert_eq!(gdb.send("s"), "S05");
+        assert_eq!(gdb.send("p0"), "0800000000000000");
+        assert_eq!(gdb.send("c"), "W00");
+        assert_eq!(rx.recv().unwrap(), 11);
+
+        assert_eq!(gdb.send("D"), "OK");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn bad_requests_and_faults() {
+        // outputs 7, then hits an invalid opcode
+        let (mut gdb, server, rx) = serve("104,7,98");
+
+        let features = gdb.send("qSupported");
+        assert!(features.contains("qXfer:features:read+"));
+        let xml = gdb.send("qXfer:features:read:target.xml:0,fff");
+        assert!(xml.starts_with("l<?xml"));
+        assert!(xml.contains(r#"<reg name="base""#));
+        assert_eq!(gdb.send("qXfer:features:read:target.xml:0,5"), "m<?xml");
+        assert_eq!(gdb.send("qXfer:features:read:other.xml:0,5"), "E00");
+
+        // overflowing, or too large for a single packet
+        assert_eq!(gdb.send("mffffffffffffffff,10"), "E01");
+        assert_eq!(gdb.send("m0,ffffffffffffffff"), "E01");
+        assert_eq!(gdb.send("m0,801"), "E01");
+        assert_eq!(gdb.send("m0,800").len(), 0x1000);
+        assert_eq!(gdb.send("Mffffffffffffffff,1:00"), "E01");
+
+        // the error is printed to the console before the stop reply
+        let console = gdb.send("c");
+        let msg = parse_bytes(&console[1..]).unwrap();
+        assert_eq!(
+            String::from_utf8(msg).unwrap(),
+            "intcode fault: Encountered unknown opcode: 98\n"
+        );
+        assert_eq!(gdb.recv(), "S04");
+        assert_eq!(gdb.send("?"), "S04");
+        assert_eq!(rx.recv().unwrap(), 7);
+
+        assert_eq!(gdb.send("D"), "OK");
+        server.join().unwrap();
+    }
+}
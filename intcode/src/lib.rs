@@ -1,7 +1,13 @@
 mod cpu;
 mod error;
+#[cfg(feature = "gdb")]
+pub mod gdb;
 mod mem;
 pub mod run;
+pub mod search;
+mod stats;
 
 pub use cpu::Intcode;
 pub use error::{Error, Result};
+pub use mem::{HexDump, Mem, MemDiff};
+pub use stats::{Stats, Status};
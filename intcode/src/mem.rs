@@ -1,16 +1,63 @@
 use std::collections::HashMap;
+use std::fmt::{self, Display};
+use std::sync::{Arc, OnceLock};
 
 use super::{Error, Result};
 
+const PAGE_BITS: usize = 6;
+const PAGE_SIZE: usize = 1 << PAGE_BITS;
+
+/// A page of memory, along with a bitmask of which of it's words have been
+/// touched (i.e: read or written, or part of the initial intcode program).
+#[derive(Debug, Clone)]
+struct Page {
+    words: [isize; PAGE_SIZE],
+    touched: u64,
+}
+
+// the touched bitmask needs a bit per word
+const _: () = assert!(PAGE_SIZE <= 64);
+
+impl Page {
+    fn new() -> Page {
+        Page {
+            words: [0; PAGE_SIZE],
+            touched: 0,
+        }
+    }
+
+    fn is_touched(&self, i: usize) -> bool {
+        self.touched & (1 << i) != 0
+    }
+}
+
 /// An Intcode machine memory module.
 ///
-/// Uses a fixed-size Vec to store the base intcode program ("low memory"), with
-/// a HashMap for any spill-over ("high memory")
+/// Memory is split into fixed-size copy-on-write pages. A dense Vec of pages
+/// backs the base intcode program ("low memory"), with a HashMap of pages for
+/// any spill-over ("high memory").
+///
+/// Pages (and the page tables themselves) are reference counted, so cloning a
+/// `Mem` is O(1), and only the pages which are subsequently written to are
+/// copied.
 #[derive(Debug, Clone)]
 pub struct Mem {
-    orig_mem: Vec<isize>,
-    lo_mem: Vec<isize>,
-    hi_mem: HashMap<usize, isize>,
+    orig_mem: Arc<Vec<isize>>,
+    orig_pages: Arc<Vec<Arc<Page>>>,
+    lo_pages: Arc<Vec<Arc<Page>>>,
+    hi_pages: Arc<HashMap<usize, Arc<Page>>>,
+    /// Contiguous copy of low memory, built by [`Mem::lo_mem`] and dropped
+    /// whenever low memory is written to
+    lo_cache: OnceLock<Arc<Vec<isize>>>,
+    peak_hi_mem: usize,
+}
+
+/// A single memory location which differs between two memory snapshots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemDiff {
+    pub addr: usize,
+    pub old: isize,
+    pub new: isize,
 }
 
 impl Mem {
@@ -24,17 +71,38 @@ impl Mem {
             .collect::<std::result::Result<Vec<_>, _>>()
             .map_err(|_| Error::ParseMem)?;
 
+        let pages = mem
+            .chunks(PAGE_SIZE)
+            .map(|chunk| {
+                let mut page = Page::new();
+                page.words[..chunk.len()].copy_from_slice(chunk);
+                page.touched = u64::MAX >> (64 - chunk.len());
+                Arc::new(page)
+            })
+            .collect::<Vec<_>>();
+        let pages = Arc::new(pages);
+
         Ok(Mem {
-            orig_mem: mem.clone(),
-            lo_mem: mem,
-            hi_mem: HashMap::new(),
+            orig_mem: Arc::new(mem),
+            orig_pages: pages.clone(),
+            lo_pages: pages,
+            hi_pages: Arc::new(HashMap::new()),
+            lo_cache: OnceLock::new(),
+            peak_hi_mem: 0,
         })
     }
 
-    /// Resets memory back to it's initial state
+    /// Resets memory back to it's initial state (though
+    /// [`Mem::peak_hi_mem_len`] keeps it's value)
     pub fn reset(&mut self) {
-        self.lo_mem.copy_from_slice(&self.orig_mem);
-        self.hi_mem.clear();
+        self.lo_pages = self.orig_pages.clone();
+        self.hi_pages = Arc::new(HashMap::new());
+        self.lo_cache.take();
+    }
+
+    /// Resets the peak high memory usage back to the current usage
+    pub(crate) fn reset_peak(&mut self) {
+        self.peak_hi_mem = self.hi_mem_len();
     }
 
     /// Returns the length of the initial intcode program
@@ -42,23 +110,312 @@ impl Mem {
         self.orig_mem.len()
     }
 
+    /// Returns the number of words allocated in high memory
+    pub fn hi_mem_len(&self) -> usize {
+        self.hi_pages.len() * PAGE_SIZE
+    }
+
+    /// Returns the peak number of words allocated in high memory since the
+    /// memory was created
+    pub fn peak_hi_mem_len(&self) -> usize {
+        self.peak_hi_mem
+    }
+
     /// Read the integer at `addr`, silently growing memory if the addr hasn't
     /// been initialized yet.
     pub fn read(&mut self, addr: usize) -> isize {
-        match self.lo_mem.get(addr) {
-            Some(v) => *v,
-            None => *self.hi_mem.entry(addr).or_default(),
+        let i = addr % PAGE_SIZE;
+        match self.page(addr >> PAGE_BITS) {
+            Some(page) if page.is_touched(i) => page.words[i],
+            // only copy the page the first time an address is touched
+            _ => {
+                let page = Arc::make_mut(self.page_mut(addr >> PAGE_BITS));
+                page.touched |= 1 << i;
+                page.words[i]
+            }
         }
     }
 
     /// Write the integer `val` to `addr`, silently growing memory if the addr
     /// hasn't been initialized yet.
     pub fn write(&mut self, addr: usize, val: isize) {
-        match self.lo_mem.get_mut(addr) {
-            Some(v) => *v = val,
+        let i = addr % PAGE_SIZE;
+        let page = Arc::make_mut(self.page_mut(addr >> PAGE_BITS));
+        page.words[i] = val;
+        page.touched |= 1 << i;
+        if addr < self.orig_mem.len() {
+            self.lo_cache.take();
+        }
+    }
+
+    /// Read the integer at `addr` without growing memory. Uninitialized
+    /// addresses read as 0.
+    pub fn peek(&self, addr: usize) -> isize {
+        match self.page(addr >> PAGE_BITS) {
+            Some(page) => page.words[addr % PAGE_SIZE],
+            None => 0,
+        }
+    }
+
+    /// Returns the page at index `idx`, or None if it hasn't been allocated.
+    fn page(&self, idx: usize) -> Option<&Arc<Page>> {
+        match self.lo_pages.get(idx) {
+            Some(page) => Some(page),
+            None => self.hi_pages.get(&idx),
+        }
+    }
+
+    /// Returns the page at index `idx`, allocating it if necessary.
+    fn page_mut(&mut self, idx: usize) -> &mut Arc<Page> {
+        match self.lo_pages.get(idx) {
+            Some(_) => &mut Arc::make_mut(&mut self.lo_pages)[idx],
             None => {
-                self.hi_mem.insert(addr, val);
+                let hi_pages = Arc::make_mut(&mut self.hi_pages);
+                let len = hi_pages.len() + usize::from(!hi_pages.contains_key(&idx));
+                self.peak_hi_mem = self.peak_hi_mem.max(len * PAGE_SIZE);
+                hi_pages.entry(idx).or_insert_with(|| Arc::new(Page::new()))
             }
         }
     }
+
+    /// Iterate over every allocated page as `(base_addr, words)` pairs, in
+    /// ascending address order.
+    pub fn pages(&self) -> impl Iterator<Item = (usize, &[isize])> + '_ {
+        self.raw_pages().map(|(base, page)| (base, &page.words[..]))
+    }
+
+    /// Like [`Mem::pages`], but yielding the pages themselves.
+    fn raw_pages(&self) -> impl Iterator<Item = (usize, &Page)> + '_ {
+        let mut hi_pages = self.hi_pages.iter().collect::<Vec<_>>();
+        hi_pages.sort_unstable_by_key(|(&idx, _)| idx);
+
+        (self.lo_pages.iter().enumerate())
+            .chain(hi_pages.into_iter().map(|(&idx, page)| (idx, page)))
+            .map(|(idx, page)| (idx << PAGE_BITS, &**page))
+    }
+
+    /// Returns a view of low memory (i.e: the region backing the initial
+    /// intcode program).
+    ///
+    /// Low memory is split across pages, so the view is assembled on first
+    /// use, and reused until low memory is next written to.
+    pub fn lo_mem(&self) -> &[isize] {
+        self.lo_cache.get_or_init(|| {
+            let mut lo_mem = self
+                .pages()
+                .take(self.lo_pages.len())
+                .flat_map(|(_, words)| words.iter().copied())
+                .collect::<Vec<_>>();
+            lo_mem.truncate(self.base_len());
+            Arc::new(lo_mem)
+        })
+    }
+
+    /// Returns a view of the initial intcode program.
+    pub fn orig_mem(&self) -> &[isize] {
+        &self.orig_mem
+    }
+
+    /// Iterate over every touched `(addr, val)` pair in ascending address
+    /// order. This includes all of low memory, and any high memory addresses
+    /// which have been read from or written to.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, isize)> + '_ {
+        self.raw_pages().flat_map(|(base, page)| {
+            (0..PAGE_SIZE)
+                .filter(move |&i| page.is_touched(i))
+                .map(move |i| (base + i, page.words[i]))
+        })
+    }
+
+    /// Returns every address which differs between `other` (old) and `self`
+    /// (new), in ascending address order.
+    ///
+    /// Pages which are still shared between the two snapshots are skipped
+    /// without being compared.
+    pub fn diff(&self, other: &Mem) -> Vec<MemDiff> {
+        let mut idxs = (self.hi_pages.keys())
+            .chain(other.hi_pages.keys())
+            .copied()
+            .chain(0..self.lo_pages.len().max(other.lo_pages.len()))
+            .collect::<Vec<_>>();
+        idxs.sort_unstable();
+        idxs.dedup();
+
+        let mut diff = Vec::new();
+        for idx in idxs {
+            let (new, old) = (self.page(idx), other.page(idx));
+            if let (Some(new), Some(old)) = (new, old) {
+                if Arc::ptr_eq(new, old) {
+                    continue;
+                }
+            }
+
+            for i in 0..PAGE_SIZE {
+                let new = new.map(|p| p.words[i]).unwrap_or(0);
+                let old = old.map(|p| p.words[i]).unwrap_or(0);
+                if new != old {
+                    let addr = (idx << PAGE_BITS) + i;
+                    diff.push(MemDiff { addr, old, new })
+                }
+            }
+        }
+        diff
+    }
+
+    /// Returns every address which differs from the initial intcode program,
+    /// in ascending address order.
+    pub fn diff_orig(&self) -> Vec<MemDiff> {
+        let mut orig = self.clone();
+        orig.reset();
+        self.diff(&orig)
+    }
+
+    /// Returns a [`Display`]-able hexdump-style view of all touched memory.
+    pub fn hexdump(&self) -> HexDump<'_> {
+        HexDump { mem: self }
+    }
+}
+
+/// A hexdump-style formatter for [`Mem`], created by [`Mem::hexdump`].
+///
+/// Each row lists 8 consecutive words, prefixed by the address of the first
+/// word. Untouched words are printed as `.`, and runs of untouched rows are
+/// collapsed into a single `*` line.
+pub struct HexDump<'a> {
+    mem: &'a Mem,
+}
+
+impl Display for HexDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const ROW: usize = 8;
+
+        let mut row_start = None;
+        let mut row = [None; ROW];
+        let mut skipped = false;
+
+        let flush_row =
+            |f: &mut fmt::Formatter<'_>, start: usize, row: &[Option<isize>]| -> fmt::Result {
+                write!(f, "{:08x}:", start)?;
+                for v in row {
+                    match v {
+                        Some(v) => write!(f, " {:>8}", v)?,
+                        None => write!(f, " {:>8}", ".")?,
+                    }
+                }
+                writeln!(f)
+            };
+
+        for (addr, val) in self.mem.iter() {
+            let start = addr - addr % ROW;
+            match row_start {
+                Some(s) if s == start => {}
+                Some(s) => {
+                    flush_row(f, s, &row)?;
+                    row = [None; ROW];
+                    skipped = start > s + ROW;
+                }
+                None => skipped = start != 0,
+            }
+            if skipped {
+                writeln!(f, "*")?;
+                skipped = false;
+            }
+            row_start = Some(start);
+            row[addr % ROW] = Some(val);
+        }
+
+        if let Some(s) = row_start {
+            flush_row(f, s, &row)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_orig() {
+        let mut mem = Mem::new("1,0,0,0,99").unwrap();
+        mem.write(0, 2);
+        mem.write(100, 7);
+        mem.read(200);
+
+        assert_eq!(
+            mem.diff_orig(),
+            vec![
+                MemDiff {
+                    addr: 0,
+                    old: 1,
+                    new: 2
+                },
+                MemDiff {
+                    addr: 100,
+                    old: 0,
+                    new: 7
+                },
+            ]
+        );
+        assert_eq!(mem.peek(200), 0);
+        assert_eq!(mem.iter().count(), 7);
+    }
+
+    #[test]
+    fn diff_snapshot() {
+        let mut mem = Mem::new("1,0,0,0,99").unwrap();
+        mem.write(50, 3);
+        let snapshot = mem.clone();
+        mem.write(1, 5);
+        mem.write(50, 4);
+
+        assert_eq!(
+            mem.diff(&snapshot),
+            vec![
+                MemDiff {
+                    addr: 1,
+                    old: 0,
+                    new: 5
+                },
+                MemDiff {
+                    addr: 50,
+                    old: 3,
+                    new: 4
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn clone_on_write() {
+        let mut mem = Mem::new("1,0,0,0,99").unwrap();
+        mem.write(1000, 1);
+        let mut fork = mem.clone();
+        fork.write(1, 9);
+        fork.write(1000, 2);
+
+        assert_eq!((mem.read(1), mem.read(1000)), (0, 1));
+        assert_eq!((fork.read(1), fork.read(1000)), (9, 2));
+        assert_eq!(fork.lo_mem(), &[1, 9, 0, 0, 99]);
+        fork.write(2, 7);
+        assert_eq!(fork.lo_mem(), &[1, 9, 7, 0, 99]);
+
+        fork.reset();
+        assert!(fork.diff_orig().is_empty());
+        assert!(Arc::ptr_eq(&fork.lo_pages, &mem.orig_pages));
+    }
+
+    #[test]
+    fn hexdump() {
+        let mut mem = Mem::new("1,0,0,0,99").unwrap();
+        mem.write(42, -1);
+
+        let expected = "\
+00000000:        1        0        0        0       99        .        .        .
+*
+00000028:        .        .       -1        .        .        .        .        .
+";
+        assert_eq!(mem.hexdump().to_string(), expected);
+    }
 }
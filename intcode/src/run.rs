@@ -1,21 +1,25 @@
 //! A collection of intcode runners for various common use-cases.
+//!
+//! Each runner returns the [`Stats`] accumulated over the course of the run.
 
-use crate::{Intcode, Result};
+use crate::{Intcode, Result, Stats};
 
 use std::collections::VecDeque;
 
 /// Run the intcode interpreter without any I/O, returning an error if any read
 /// or write instruction is encountered.
-pub fn headless(intcode: &mut Intcode) -> Result<()> {
+pub fn headless(intcode: &mut Intcode) -> Result<Stats> {
+    let start = intcode.stats();
     while intcode.step(
         || Err("intcode cannot read input in headless mode".into()),
         |_| Err("intcode cannot write output in headless mode".into()),
     )? {}
-    Ok(())
+    Ok(intcode.stats().since(&start))
 }
 
 /// Run the intcode interpreter using stdin for input, and stdout for output.
-pub fn interactively(intcode: &mut Intcode) -> Result<()> {
+pub fn interactively(intcode: &mut Intcode) -> Result<Stats> {
+    let start = intcode.stats();
     while intcode.step(
         || {
             print!("> ");
@@ -29,7 +33,7 @@ pub fn interactively(intcode: &mut Intcode) -> Result<()> {
             Ok(())
         },
     )? {}
-    Ok(())
+    Ok(intcode.stats().since(&start))
 }
 
 /// Run the intcode interpreter to completion using the provided input and
@@ -38,7 +42,8 @@ pub fn to_completion(
     intcode: &mut Intcode,
     input: &mut Vec<isize>,
     output: &mut Vec<isize>,
-) -> Result<()> {
+) -> Result<Stats> {
+    let start = intcode.stats();
     input.reverse();
 
     while intcode.step(
@@ -52,13 +57,17 @@ pub fn to_completion(
             Ok(())
         },
     )? {}
-    Ok(())
+    Ok(intcode.stats().since(&start))
 }
 
 /// Run the intcode interpreter with the provided input until the machine
 /// has outputted `n` values. If the machine halts, None is returned.
 /// Returns an error if the input VecDeque is exhausted.
-pub fn until_output(intcode: &mut Intcode, input: &mut VecDeque<isize>) -> Result<Option<isize>> {
+pub fn until_output(
+    intcode: &mut Intcode,
+    input: &mut VecDeque<isize>,
+) -> Result<(Option<isize>, Stats)> {
+    let start = intcode.stats();
     let mut output = None;
     loop {
         let running = intcode.step(
@@ -74,11 +83,102 @@ pub fn until_output(intcode: &mut Intcode, input: &mut VecDeque<isize>) -> Resul
         )?;
 
         if !running {
-            return Ok(None);
+            return Ok((None, intcode.stats().since(&start)));
         }
 
         if let Some(output) = output {
-            return Ok(Some(output));
+            return Ok((Some(output), intcode.stats().since(&start)));
+        }
+    }
+}
+
+/// Run the intcode interpreter with the provided input until the machine
+/// requests input after the input VecDeque is exhausted, appending any outputs
+/// to the provided output buffer. The returned stats' `status` indicates
+/// whether the machine halted, or is waiting on more input.
+pub fn until_input(
+    intcode: &mut Intcode,
+    input: &mut VecDeque<isize>,
+    output: &mut Vec<isize>,
+) -> Result<Stats> {
+    let start = intcode.stats();
+    loop {
+        if input.is_empty() && intcode.needs_input() {
+            return Ok(intcode.stats().since(&start));
         }
+
+        let running = intcode.step(
+            || {
+                input
+                    .pop_front()
+                    .ok_or_else(|| "no more input in the input buffer".into())
+            },
+            |i| {
+                output.push(i);
+                Ok(())
+            },
+        )?;
+
+        if !running {
+            return Ok(intcode.stats().since(&start));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::Status;
+
+    #[test]
+    fn stats() {
+        // reads an input, outputs it twice, and halts
+        let intcode = &mut Intcode::new("3,7,4,7,4,7,99,0").unwrap();
+        intcode.mem().write(1000, 1);
+
+        let input = &mut VecDeque::new();
+        input.push_back(7);
+
+        let (out, stats) = until_output(intcode, input).unwrap();
+        assert_eq!(out, Some(7));
+        assert_eq!((stats.instructions, stats.inputs, stats.outputs), (2, 1, 1));
+        assert_eq!(stats.status, Status::Suspended);
+
+        let stats = until_input(intcode, input, &mut Vec::new()).unwrap();
+        assert_eq!((stats.instructions, stats.inputs, stats.outputs), (2, 0, 1));
+        assert_eq!(stats.opcode_count(4), 1);
+        assert_eq!(stats.opcode_count(99), 1);
+        assert_eq!(stats.status, Status::Halted);
+        assert_eq!(stats.peak_hi_mem, 64);
+
+        assert_eq!(intcode.stats().instructions, 4);
+    }
+
+    #[test]
+    fn failed_input_isnt_counted() {
+        let intcode = &mut Intcode::new("3,0,99").unwrap();
+        assert!(until_output(intcode, &mut VecDeque::new()).is_err());
+
+        let stats = intcode.stats();
+        assert_eq!((stats.instructions, stats.inputs), (0, 0));
+        assert_eq!(stats.peak_hi_mem, 0);
+    }
+
+    #[test]
+    fn peak_hi_mem() {
+        let intcode = &mut Intcode::new("3,7,4,7,4,7,99,0").unwrap();
+        intcode.mem().write(1000, 1);
+        intcode.mem().write(2000, 1);
+        intcode.mem().reset();
+        assert_eq!(intcode.stats().peak_hi_mem, 128);
+
+        // memory freed before the run started doesn't count towards it's peak
+        let (_, stats) = until_output(intcode, &mut VecDeque::from(vec![1])).unwrap();
+        assert_eq!(stats.peak_hi_mem, 0);
+        assert_eq!(intcode.stats().peak_hi_mem, 128);
+
+        intcode.reset();
+        assert_eq!(intcode.stats().peak_hi_mem, 0);
     }
 }
@@ -0,0 +1,426 @@
+//! Search over the state space of an intcode program by forking the machine
+//! at every branch.
+//!
+//! Each action is a sequence of inputs. After an action's inputs are fed into
+//! a forked machine, the machine runs until it requests more input (or halts),
+//! and the produced outputs are handed to an interpretation callback which
+//! decides what to do with the resulting state.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashSet, VecDeque};
+use std::error::Error as StdError;
+use std::hash::Hash;
+use std::result::Result as StdResult;
+
+use crate::{run, Error, Intcode, Result, Status};
+
+/// The result of interpreting a machine's output after performing an action.
+#[derive(Debug)]
+pub enum Visit<S> {
+    /// Keep exploring from the new state.
+    Continue(S),
+    /// Stop the search, returning the new state.
+    Goal(S),
+    /// Don't explore any further down this branch.
+    Prune,
+}
+
+/// Return type of the output-interpretation callback.
+pub type VisitResult<S> = StdResult<Visit<S>, Box<dyn StdError>>;
+
+/// Order in which states are explored.
+pub enum Strategy<H> {
+    /// Breadth-first. Finds the path with the fewest actions.
+    Bfs,
+    /// Depth-first.
+    Dfs,
+    /// A*, using the provided heuristic to estimate the number of actions
+    /// remaining until a goal. Finds the path with the fewest actions as long
+    /// as the heuristic never overestimates.
+    AStar(H),
+}
+
+/// A goal state found by a search.
+#[derive(Debug)]
+pub struct Found<'a, S> {
+    /// The machine, just after the final action was performed.
+    pub intcode: Intcode,
+    /// The state returned by the interpretation callback.
+    pub state: S,
+    /// The sequence of actions which lead to the goal.
+    pub path: Vec<&'a [isize]>,
+}
+
+/// Perform a breadth-first search. See [`search`].
+pub fn bfs<'a, S, K: Hash + Eq>(
+    intcode: Intcode,
+    state: S,
+    actions: &'a [&'a [isize]],
+    interpret: impl FnMut(&S, &[isize], &[isize]) -> VisitResult<S>,
+    key: impl Fn(&S) -> K,
+) -> Result<Option<Found<'a, S>>> {
+    search(
+        Strategy::<fn(&S) -> usize>::Bfs,
+        intcode,
+        state,
+        actions,
+        interpret,
+        key,
+    )
+}
+
+/// Perform a depth-first search. See [`search`].
+pub fn dfs<'a, S, K: Hash + Eq>(
+    intcode: Intcode,
+    state: S,
+    actions: &'a [&'a [isize]],
+    interpret: impl FnMut(&S, &[isize], &[isize]) -> VisitResult<S>,
+    key: impl Fn(&S) -> K,
+) -> Result<Option<Found<'a, S>>> {
+    search(
+        Strategy::<fn(&S) -> usize>::Dfs,
+        intcode,
+        state,
+        actions,
+        interpret,
+        key,
+    )
+}
+
+/// Search the state space reachable from `intcode` (with the corresponding
+/// user-defined `state`) by trying every action in `actions` on a fork of the
+/// machine.
+///
+/// `interpret` is called with the parent state, the action performed, and the
+/// outputs the machine produced in response. States are de-duplicated by
+/// `key`, and each key is only explored once.
+///
+/// BFS and DFS stop as soon as a goal is found. A* only stops once a goal is
+/// popped off the frontier, since the first goal found isn't necessarily the
+/// closest one.
+///
+/// Returns `None` if the search space was exhausted without finding a goal.
+/// This makes it possible to exhaustively explore a program's state space by
+/// recording states from within `interpret` and never returning a goal.
+pub fn search<'a, S, K, H>(
+    strategy: Strategy<H>,
+    intcode: Intcode,
+    state: S,
+    actions: &'a [&'a [isize]],
+    mut interpret: impl FnMut(&S, &[isize], &[isize]) -> VisitResult<S>,
+    key: impl Fn(&S) -> K,
+) -> Result<Option<Found<'a, S>>>
+where
+    K: Hash + Eq,
+    H: Fn(&S) -> usize,
+{
+    // (parent, action) for every node, used to reconstruct paths
+    let mut nodes: Vec<(usize, usize)> = vec![(0, 0)];
+    let path_to = |nodes: &[(usize, usize)], mut id: usize| {
+        let mut path = Vec::new();
+        while id != 0 {
+            let (parent, action) = nodes[id];
+            path.push(actions[action]);
+            id = parent;
+        }
+        path.reverse();
+        path
+    };
+
+    let mut seen = HashSet::new();
+    let mut frontier = Frontier::new(strategy);
+
+    if !frontier.is_astar() {
+        seen.insert(key(&state));
+    }
+    frontier.push(Node {
+        depth: 0,
+        id: 0,
+        goal: false,
+        intcode,
+        state,
+    });
+
+    let input = &mut VecDeque::new();
+    let output = &mut Vec::new();
+
+    while let Some(Node {
+        depth,
+        id,
+        goal,
+        intcode,
+        state,
+    }) = frontier.pop()
+    {
+        if goal {
+            return Ok(Some(Found {
+                intcode,
+                state,
+                path: path_to(&nodes, id),
+            }));
+        }
+
+        if frontier.is_astar() && !seen.insert(key(&state)) {
+            continue;
+        }
+
+        for (action_idx, action) in actions.iter().enumerate() {
+            let mut fork = intcode.clone();
+            input.clear();
+            input.extend(action.iter());
+            output.clear();
+
+            let status = run::until_input(&mut fork, input, output)?.status;
+            let new_state =
+                match interpret(&state, action, output).map_err(Error::InterpretError)? {
+                    Visit::Prune => continue,
+                    Visit::Goal(new_state) if !frontier.is_astar() => {
+                        nodes.push((id, action_idx));
+                        return Ok(Some(Found {
+                            intcode: fork,
+                            state: new_state,
+                            path: path_to(&nodes, nodes.len() - 1),
+                        }));
+                    }
+                    Visit::Goal(new_state) => {
+                        nodes.push((id, action_idx));
+                        frontier.push(Node {
+                            depth: depth + 1,
+                            id: nodes.len() - 1,
+                            goal: true,
+                            intcode: fork,
+                            state: new_state,
+                        });
+                        continue;
+                    }
+                    Visit::Continue(new_state) => new_state,
+                };
+
+            // a halted machine can't perform any more actions
+            if status == Status::Halted {
+                continue;
+            }
+
+            if !frontier.is_astar() && !seen.insert(key(&new_state)) {
+                continue;
+            }
+
+            nodes.push((id, action_idx));
+            frontier.push(Node {
+                depth: depth + 1,
+                id: nodes.len() - 1,
+                goal: false,
+                intcode: fork,
+                state: new_state,
+            });
+        }
+    }
+
+    Ok(None)
+}
+
+/// A state which has yet to be explored.
+struct Node<S> {
+    depth: usize,
+    id: usize,
+    /// Whether the state is a goal (only used by A*)
+    goal: bool,
+    intcode: Intcode,
+    state: S,
+}
+
+/// A node in the A* priority queue, ordered by (lowest) estimated total cost,
+/// and then by insertion order.
+struct Prioritized<S> {
+    cost: usize,
+    seq: usize,
+    node: Node<S>,
+}
+
+impl<S> Prioritized<S> {
+    fn key(&self) -> Reverse<(usize, usize)> {
+        Reverse((self.cost, self.seq))
+    }
+}
+
+impl<S> PartialEq for Prioritized<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl<S> Eq for Prioritized<S> {}
+
+impl<S> PartialOrd for Prioritized<S> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<S> Ord for Prioritized<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Storage for the set of states which have yet to be explored.
+enum Frontier<S, H> {
+    Bfs(VecDeque<Node<S>>),
+    Dfs(Vec<Node<S>>),
+    AStar {
+        heuristic: H,
+        heap: BinaryHeap<Prioritized<S>>,
+        seq: usize,
+    },
+}
+
+impl<S, H: Fn(&S) -> usize> Frontier<S, H> {
+    fn new(strategy: Strategy<H>) -> Frontier<S, H> {
+        match strategy {
+            Strategy::Bfs => Frontier::Bfs(VecDeque::new()),
+            Strategy::Dfs => Frontier::Dfs(Vec::new()),
+            Strategy::AStar(heuristic) => Frontier::AStar {
+                heuristic,
+                heap: BinaryHeap::new(),
+                seq: 0,
+            },
+        }
+    }
+
+    fn is_astar(&self) -> bool {
+        matches!(self, Frontier::AStar { .. })
+    }
+
+    fn push(&mut self, node: Node<S>) {
+        match self {
+            Frontier::Bfs(q) => q.push_back(node),
+            Frontier::Dfs(s) => s.push(node),
+            Frontier::AStar {
+                heuristic,
+                heap,
+                seq,
+            } => {
+                let remaining = if node.goal { 0 } else { heuristic(&node.state) };
+                let cost = node.depth + remaining;
+                heap.push(Prioritized {
+                    cost,
+                    seq: *seq,
+                    node,
+                });
+                *seq += 1;
+            }
+        }
+    }
+
+    fn pop(&mut self) -> Option<Node<S>> {
+        match self {
+            Frontier::Bfs(q) => q.pop_front(),
+            Frontier::Dfs(s) => s.pop(),
+            Frontier::AStar { heap, .. } => heap.pop().map(|p| p.node),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // reads an input, outputs it, and loops
+    const ECHO: &str = "3,7,4,7,1105,1,0,0";
+
+    fn step_to(target: isize) -> impl FnMut(&isize, &[isize], &[isize]) -> VisitResult<isize> {
+        move |sum, _, output| {
+            let sum = sum + output.first().ok_or("expected output")?;
+            Ok(match sum {
+                s if s == target => Visit::Goal(s),
+                s if s > target => Visit::Prune,
+                s => Visit::Continue(s),
+            })
+        }
+    }
+
+    #[test]
+    fn bfs_shortest_path() {
+        let intcode = Intcode::new(ECHO).unwrap();
+        let found = bfs(intcode, 0, &[&[1], &[2]], step_to(5), |&s| s)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(found.state, 5);
+        assert_eq!(found.path, vec![&[1][..], &[2], &[2]]);
+        assert_eq!(found.intcode.mem_ref().peek(7), 2);
+    }
+
+    #[test]
+    fn interpret_errors() {
+        let intcode = Intcode::new(ECHO).unwrap();
+        match dfs(intcode, 0, &[&[1]], |_, _, _| Err("bruh".into()), |&s| s) {
+            Err(Error::InterpretError(e)) => assert_eq!(e.to_string(), "bruh"),
+            _ => panic!("expected an InterpretError"),
+        }
+    }
+
+    #[test]
+    fn astar_shortest_path() {
+        let intcode = Intcode::new(ECHO).unwrap();
+        let heuristic = |&s: &isize| ((9 - s) / 3) as usize;
+        let found = search(
+            Strategy::AStar(heuristic),
+            intcode,
+            0,
+            &[&[1], &[3]],
+            step_to(9),
+            |&s| s,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(found.path.len(), 3);
+    }
+
+    #[test]
+    fn astar_checks_goals_when_popped() {
+        // root -> A1 -> A2 -> A3 -> GA, and root -> B1 -> B2 -> GB. GA is
+        // generated first, but GB is closer.
+        let graph = |state: &&'static str, _: &[isize], output: &[isize]| {
+            let next = match (*state, output[0]) {
+                ("root", 1) => Visit::Continue("A1"),
+                ("root", 2) => Visit::Continue("B1"),
+                ("A1", 1) => Visit::Continue("A2"),
+                ("A2", 1) => Visit::Continue("A3"),
+                ("A3", 1) => Visit::Goal("GA"),
+                ("B1", 1) => Visit::Continue("B2"),
+                ("B2", 1) => Visit::Goal("GB"),
+                _ => Visit::Prune,
+            };
+            Ok(next)
+        };
+        let heuristic = |state: &&'static str| match *state {
+            "B1" => 2,
+            "B2" => 1,
+            _ => 0,
+        };
+
+        let intcode = Intcode::new(ECHO).unwrap();
+        let found = search(
+            Strategy::AStar(heuristic),
+            intcode,
+            "root",
+            &[&[1], &[2]],
+            graph,
+            |&s| s,
+        )
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(found.state, "GB");
+        assert_eq!(found.path, vec![&[2][..], &[1], &[1]]);
+    }
+
+    #[test]
+    fn exhaustive() {
+        let intcode = Intcode::new(ECHO).unwrap();
+        let found = dfs(intcode, 0, &[&[1], &[2]], step_to(-1), |&s| s).unwrap();
+        assert!(found.is_none());
+    }
+}
@@ -0,0 +1,127 @@
+use std::fmt::{self, Display};
+
+use super::cpu::Instruction;
+
+/// Opcodes in the order they're stored in [`Stats`].
+const OPCODES: [usize; 10] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 99];
+
+/// Whether a machine halted, or was suspended (e.g: waiting on I/O) at the end
+/// of a run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Halted,
+    Suspended,
+}
+
+/// Execution statistics for an intcode machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stats {
+    /// Number of instructions executed
+    pub instructions: usize,
+    /// Number of input values consumed
+    pub inputs: usize,
+    /// Number of output values produced
+    pub outputs: usize,
+    /// Peak number of words allocated in high memory
+    pub peak_hi_mem: usize,
+    /// Whether the machine halted
+    pub status: Status,
+    opcodes: [usize; 10],
+    /// Number of words allocated in high memory when the stats were taken
+    pub(crate) hi_mem: usize,
+}
+
+impl Default for Stats {
+    fn default() -> Stats {
+        Stats {
+            instructions: 0,
+            inputs: 0,
+            outputs: 0,
+            peak_hi_mem: 0,
+            status: Status::Suspended,
+            opcodes: [0; 10],
+            hi_mem: 0,
+        }
+    }
+}
+
+impl Stats {
+    /// Record the execution of an instruction
+    pub(crate) fn record(&mut self, instr: &Instruction) {
+        use Instruction::*;
+
+        let idx = match instr {
+            Add_(..) => 0,
+            Mul_(..) => 1,
+            Geti(..) => {
+                self.inputs += 1;
+                2
+            }
+            Puti(..) => {
+                self.outputs += 1;
+                3
+            }
+            Jnz_(..) => 4,
+            Jz__(..) => 5,
+            Cmp_(..) => 6,
+            Eq__(..) => 7,
+            Setb(..) => 8,
+            Halt => {
+                self.status = Status::Halted;
+                9
+            }
+        };
+
+        self.instructions += 1;
+        self.opcodes[idx] += 1;
+    }
+
+    /// Returns the number of times instructions with `opcode` were executed
+    pub fn opcode_count(&self, opcode: usize) -> usize {
+        match OPCODES.iter().position(|&o| o == opcode) {
+            Some(idx) => self.opcodes[idx],
+            None => 0,
+        }
+    }
+
+    /// Iterate over `(opcode, count)` pairs for every executed opcode
+    pub fn opcode_counts(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (OPCODES.iter().copied())
+            .zip(self.opcodes.iter().copied())
+            .filter(|&(_, n)| n != 0)
+    }
+
+    /// Returns the statistics accumulated since the `start` snapshot.
+    pub(crate) fn since(&self, start: &Stats) -> Stats {
+        let mut opcodes = self.opcodes;
+        for (n, start) in opcodes.iter_mut().zip(start.opcodes.iter()) {
+            *n -= start;
+        }
+
+        Stats {
+            instructions: self.instructions - start.instructions,
+            inputs: self.inputs - start.inputs,
+            outputs: self.outputs - start.outputs,
+            // high memory is never freed during a run, so it's peak over the
+            // run is just it's final size
+            peak_hi_mem: self.hi_mem,
+            status: self.status,
+            opcodes,
+            hi_mem: self.hi_mem,
+        }
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} after {} instructions ({} in, {} out, {} hi-mem words)",
+            self.status, self.instructions, self.inputs, self.outputs, self.peak_hi_mem
+        )?;
+        for (opcode, n) in self.opcode_counts() {
+            write!(f, "\n  {:>2}: {}", opcode, n)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+//! Typed solution answers.
+
+use std::fmt::{self, Display};
+
+use crate::harness::json::{Json, ToJson};
+use crate::DynResult;
+
+/// A value returned from a day's solution.
+///
+/// Solutions often compute more than just the number AoC asks for (e.g: which
+/// phase settings produced the max thruster signal), and some answers can only
+/// be read off an image. This trait splits a return value into it's
+/// submittable answer, any auxiliary details, and any rendered image.
+pub trait Answer: ToJson {
+    /// The canonical answer, as it would be submitted. `None` if the answer
+    /// can't be extracted automatically (e.g: it's drawn in an image which
+    /// couldn't be read).
+    fn answer(&self) -> Option<String>;
+
+    /// Auxiliary data computed alongside the answer.
+    fn details(&self) -> Option<String> {
+        None
+    }
+
+    /// A rendered image.
+    fn image(&self) -> Option<String> {
+        None
+    }
+}
+
+macro_rules! answer_scalar_impl {
+    ($($type:ty),*) => ($(
+        impl Answer for $type {
+            fn answer(&self) -> Option<String> {
+                Some(self.to_string())
+            }
+        }
+    )*)
+}
+
+answer_scalar_impl! { i32, i64, isize, u32, u64, usize, String }
+
+/// Solutions without an answer (e.g: unimplemented ones)
+impl Answer for () {
+    fn answer(&self) -> Option<String> {
+        None
+    }
+}
+
+/// An answer, along with the data used to derive it
+impl<A: Display + ToJson, B: fmt::Debug + ToJson> Answer for (A, B) {
+    fn answer(&self) -> Option<String> {
+        Some(self.0.to_string())
+    }
+
+    fn details(&self) -> Option<String> {
+        Some(format!("{:?}", self.1))
+    }
+}
+
+/// The full output of an intcode diagnostic program, where the final output
+/// is the answer
+impl Answer for Vec<isize> {
+    fn answer(&self) -> Option<String> {
+        self.last().map(|x| x.to_string())
+    }
+
+    fn details(&self) -> Option<String> {
+        if self.len() > 1 {
+            Some(format!("{:?}", self))
+        } else {
+            None
+        }
+    }
+}
+
+/// A black and white image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    pixels: Vec<bool>,
+}
+
+impl Image {
+    /// Create a new image from a row-major list of pixels. Returns an error
+    /// if the pixels can't be split into rows of `width` pixels.
+    pub fn new(width: usize, pixels: Vec<bool>) -> DynResult<Image> {
+        if width == 0 {
+            return Err("image width must be non-zero".into());
+        }
+        if !pixels.len().is_multiple_of(width) {
+            return Err(format!(
+                "{} pixels can't be split into rows of {}",
+                pixels.len(),
+                width
+            )
+            .into());
+        }
+        Ok(Image { width, pixels })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.pixels.len() / self.width
+    }
+
+    /// Returns the pixel at (x, y), where (0, 0) is the top-left corner.
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Iterate over the image's rows.
+    pub fn rows(&self) -> impl Iterator<Item = &[bool]> {
+        self.pixels.chunks(self.width)
+    }
+}
+
+impl Display for Image {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, row) in self.rows().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            for &px in row {
+                write!(f, "{}", if px { 'X' } else { ' ' })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ToJson for Image {
+    fn to_json(&self) -> Json {
+        Json::Array(
+            self.to_string()
+                .lines()
+                .map(|ln| Json::String(ln.to_string()))
+                .collect(),
+        )
+    }
+}
+
+/// Images which spell out letters are read using [`ocr`](crate::ocr)
+impl Answer for Image {
+    fn answer(&self) -> Option<String> {
+        crate::ocr::read(self)
+    }
+
+    fn image(&self) -> Option<String> {
+        Some(self.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_answers() {
+        let ans = (38500isize, vec![0isize, 3, 2, 4, 1]);
+        assert_eq!(ans.answer().unwrap(), "38500");
+        assert_eq!(ans.details().unwrap(), "[0, 3, 2, 4, 1]");
+
+        let ans = vec![0isize, 0, 42];
+        assert_eq!(ans.answer().unwrap(), "42");
+
+        let img = Image::new(2, vec![true, false, false, true]).unwrap();
+        assert_eq!(img.answer(), None);
+        assert_eq!(img.image().unwrap(), "X \n X");
+
+        assert!(Image::new(0, vec![]).is_err());
+        assert!(Image::new(2, vec![true; 3]).is_err());
+    }
+}
@@ -1,30 +1,25 @@
 use crate::prelude::*;
 
-macro_rules! munge_input {
-    ($input:ident) => {{
-        let input = &$input;
-        input
-            .split('\n')
-            .enumerate()
-            .flat_map(|(row, s)| {
-                s.chars().enumerate().filter_map(move |(col, c)| {
-                    // 'X' doesn't appear in the question input, but it's useful
-                    // to annotate the station in the test-cases (for reasoning
-                    // about angles and whatnot)
-                    match c {
-                        '#' | 'X' => Some(Ok((col as isize, row as isize))),
-                        '.' => None,
-                        _ => Some(Err("invalid char in input")),
-                    }
-                })
-            })
-            // use BTree for consistent ordering
-            .collect::<Result<BTreeSet<(isize, isize)>, _>>()?
-    }};
+fn parse(input: &str) -> parse::Result<BTreeSet<(isize, isize)>> {
+    // 'X' doesn't appear in the question input, but it's useful to annotate
+    // the station in the test-cases (for reasoning about angles and whatnot)
+    let grid = parse::grid(input, |c| match c {
+        '#' | 'X' => Ok(true),
+        '.' => Ok(false),
+        _ => Err(format!("invalid character {:?}", c)),
+    })?;
+
+    let asteroids = grid
+        .iter()
+        .filter(|(_, &asteroid)| asteroid)
+        .map(|(p, _)| (p.x as isize, p.y as isize))
+        // use BTree for consistent ordering
+        .collect();
+    Ok(asteroids)
 }
 
 pub fn q1(input: String, _args: &[String]) -> DynResult<(usize, (isize, isize))> {
-    let asteroids = munge_input!(input);
+    let asteroids = parse(&input)?;
 
     let max = asteroids
         .iter()
@@ -51,7 +46,7 @@ pub fn q1(input: String, _args: &[String]) -> DynResult<(usize, (isize, isize))>
 }
 
 pub fn q2(input: String, _args: &[String]) -> DynResult<isize> {
-    let asteroids = munge_input!(input);
+    let asteroids = parse(&input)?;
 
     let (sx, sy) = q1(input.clone(), &[])?.1;
 
@@ -72,7 +67,7 @@ pub fn q2(input: String, _args: &[String]) -> DynResult<isize> {
             angle += 2. * PI;
         }
         // flip angle direction (to go clockwise)
-        angle = -angle;
+        angle = (2. * PI - angle) % (2. * PI);
 
         let magnitude = cx.pow(2) + cy.pow(2);
 
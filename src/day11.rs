@@ -1,33 +1,36 @@
 use crate::prelude::*;
 
-fn calc_path(input: String, seed: isize) -> DynResult<HashMap<(i32, i32), isize>> {
+/// Read which way the robot turns after painting a tile.
+fn turn(intcode: &mut Intcode, input: &mut VecDeque<isize>, dir: Dir) -> DynResult<Dir> {
+    match intcode::run::until_output(intcode, input)?.0 {
+        Some(0) => Ok(dir.turn_left()),
+        Some(1) => Ok(dir.turn_right()),
+        Some(rotation) => Err(format!("unknown rotation {}", rotation).into()),
+        None => Err("robot halted instead of turning".into()),
+    }
+}
+
+fn calc_path(input: String, seed: isize) -> DynResult<SparseGrid<isize>> {
     let intcode = &mut Intcode::new(input)?;
     let input = &mut VecDeque::new();
 
-    let mut tiles = HashMap::new();
+    let mut tiles = SparseGrid::new();
 
-    const DELTA: [(i32, i32); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
-    let mut delta = 0; // facing up
+    let mut dir = Dir::Up;
 
     input.push_back(seed);
-    let mut pos = (0, 0);
+    let mut pos = Point::ORIGIN;
 
-    while let Some(color) = intcode::run::until_output(intcode, input)? {
+    while let (Some(color), _) = intcode::run::until_output(intcode, input)
+        .with_context(|| format!("while painting at {}", pos))?
+    {
         // mark tile as painted with given color
         tiles.insert(pos, color);
 
-        let rotation =
-            intcode::run::until_output(intcode, input)?.ok_or("malfunctioning intcode program")?;
-
-        match rotation {
-            0 => delta = (delta + 3) % 4,
-            1 => delta = (delta + 1) % 4,
-            _ => return Err("malfunctioning intcode program".into()),
-        }
+        dir = turn(intcode, input, dir).with_context(|| format!("while turning at {}", pos))?;
 
         // move the robot forward
-        pos.0 += DELTA[delta].0;
-        pos.1 += DELTA[delta].1;
+        pos += dir;
 
         // input the current tile color the robot is standing on
         input.push_back(*tiles.entry(pos).or_default())
@@ -41,42 +44,30 @@ pub fn q1(input: String, _args: &[String]) -> DynResult<usize> {
     Ok(tiles.len())
 }
 
-pub fn q2(input: String, args: &[String]) -> DynResult<()> {
-    let seed = match args.get(0).map(|x| x.as_str()) {
-        Some("alt") => 0,
-        Some(_) => return Err("did you mean `alt`?".into()),
-        None => 1,
-    };
-
-    let tiles = calc_path(input, seed)?;
+pub const Q2_OPTS: &[Opt] = &[Opt::flag(
+    "alt",
+    "start on a black panel (i.e: re-run q1's path)",
+)];
 
-    // find output bounds
-    let min_x = *tiles.iter().map(|((x, _), _)| x).min().unwrap();
-    let min_y = *tiles.iter().map(|((_, y), _)| y).min().unwrap();
-    let max_x = *tiles.iter().map(|((x, _), _)| x).max().unwrap();
-    let max_y = *tiles.iter().map(|((_, y), _)| y).max().unwrap();
+pub fn q2(input: String, args: &[String]) -> DynResult<Image> {
+    let args = Args::parse(Q2_OPTS, args)?;
+    let seed = if args.flag("alt") { 0 } else { 1 };
 
-    let mut floor = vec![vec![0; (max_x - min_x + 1) as usize]; (max_y - min_y + 1) as usize];
-
-    for ((x, y), color) in tiles {
-        floor[(y - min_y) as usize][(x - min_x) as usize] = color;
-    }
-
-    for row in floor.into_iter().rev() {
-        println!(
-            "{}",
-            row.into_iter()
-                .map(|c| {
-                    match c {
-                        0 => " ".to_string(),
-                        1 => "X".to_string(),
-                        _ => unreachable!(),
-                    }
-                })
-                .collect::<Vec<String>>()
-                .join("")
-        );
-    }
+    let tiles = calc_path(input, seed)?;
 
-    Ok(())
+    let bounds = tiles.bounds().ok_or("robot didn't paint anything")?;
+    let pixels = bounds
+        .points()
+        .map(|p| match tiles.get(p) {
+            None | Some(0) => Ok(false),
+            Some(1) => Ok(true),
+            Some(color) => Err(format!("unknown color {} at {}", color, p)),
+        })
+        .collect::<Result<_, _>>()?;
+
+    let image = Image::new(bounds.width(), pixels)?;
+    export::image("11-2", || {
+        Raster::from_image(&image, export::WHITE, export::BLACK)
+    })?;
+    Ok(image)
 }
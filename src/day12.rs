@@ -2,28 +2,19 @@ use crate::prelude::*;
 
 // Taking a page out of game development, and using a "structure of arrays"
 // instead of an "array of structures"
-macro_rules! munge_input {
-    ($input:ident) => {{
-        let input = &$input;
-
-        let mut pos = vec![Vec::new(); 3];
-        let mut vel = vec![Vec::new(); 3];
-
-        let lines = input.split('\n').map(|s| {
-            s.chars()
-                .filter(|&c| !"<>xyz=".contains(c))
-                .collect::<String>()
-        });
-
-        for ln in lines {
-            for (i, val) in ln.split(',').map(|s| s.trim().parse::<i32>()).enumerate() {
-                pos[i].push(val?);
-                vel[i].push(0);
-            }
+fn parse(input: &str) -> parse::Result<(Vec<Vec<i32>>, Vec<Vec<i32>>)> {
+    let mut pos = vec![Vec::new(); 3];
+    let mut vel = vec![Vec::new(); 3];
+
+    for ln in parse::Span::new(input).lines() {
+        let moon = parse::record(ln.trim().strip_prefix("<")?.strip_suffix(">")?, ",")?;
+        for (i, axis) in ["x", "y", "z"].iter().enumerate() {
+            pos[i].push(moon.get(axis)?);
+            vel[i].push(0);
         }
+    }
 
-        (pos, vel)
-    }};
+    Ok((pos, vel))
 }
 
 fn iter_moon_component(pos: &mut [i32], vel: &mut [i32]) {
@@ -46,15 +37,12 @@ fn iter_moon_component(pos: &mut [i32], vel: &mut [i32]) {
     }
 }
 
+pub const Q1_OPTS: &[Opt] = &[Opt::uint("iters", "1000", "number of steps to simulate")];
+
 pub fn q1(input: String, args: &[String]) -> DynResult<i32> {
-    let iters = match args.get(0) {
-        None => 1000,
-        Some(v) => v
-            .parse::<usize>()
-            .map_err(|_| "invalid num iters specified")?,
-    };
+    let iters = Args::parse(Q1_OPTS, args)?.get::<usize>("iters")?;
 
-    let (mut pos, mut vel) = munge_input!(input);
+    let (mut pos, mut vel) = parse(&input)?;
 
     for _ in 0..iters {
         for c in 0..3 {
@@ -62,7 +50,7 @@ pub fn q1(input: String, args: &[String]) -> DynResult<i32> {
         }
     }
 
-    let count = pos.get(0).map(|v| v.len()).unwrap_or(0);
+    let count = pos.first().map(|v| v.len()).unwrap_or(0);
 
     let total_energy = (0..count)
         .map(|i| {
@@ -76,7 +64,7 @@ pub fn q1(input: String, args: &[String]) -> DynResult<i32> {
 }
 
 pub fn q2(input: String, _args: &[String]) -> DynResult<usize> {
-    let (mut pos, mut vel) = munge_input!(input);
+    let (mut pos, mut vel) = parse(&input)?;
 
     let mut iters = vec![0; 3];
 
@@ -1,23 +1,43 @@
 use crate::prelude::*;
 
+use std::time::Duration;
+
 struct GameState {
-    tiles: HashMap<(isize, isize), isize>,
+    tiles: SparseGrid<isize>,
     score: isize,
 }
 
-fn run_game(intcode: &mut Intcode, delay: u64) -> DynResult<GameState> {
+/// The color of a tile in exported images.
+fn tile_color(y: i32, kind: isize) -> export::Rgb {
+    match kind {
+        1 => export::ansi_256(8),
+        // same colors as the terminal (i.e: background colors 41 - 47)
+        2 => export::ansi_256(1 + (y % 7) as u8),
+        3 => export::WHITE,
+        4 => export::ansi_256(11),
+        _ => export::BLACK,
+    }
+}
+
+fn screen(tiles: &SparseGrid<isize>) -> Raster {
+    Raster::from_sparse(tiles, |p, kind| tile_color(p.y, *kind.unwrap_or(&0)))
+}
+
+fn run_game(intcode: &mut Intcode, vis: &mut Vis, frames: &mut Frames) -> DynResult<GameState> {
     let input = &mut VecDeque::new();
 
-    let mut tiles = HashMap::new();
+    let mut tiles = SparseGrid::new();
     let mut score = 0;
 
-    // clear screen
-    print!("\x1b[2J");
+    vis.clear()?;
 
     // AI
     let mut ball_x = 0;
     let mut paddle_x = 0;
 
+    // whether anything has been drawn since the last frame
+    let mut dirty = false;
+
     loop {
         // AI
         let cmd = if ball_x < paddle_x {
@@ -31,29 +51,34 @@ fn run_game(intcode: &mut Intcode, delay: u64) -> DynResult<GameState> {
         input.clear();
         input.push_back(cmd);
 
-        let x = intcode::run::until_output(intcode, input)?;
+        let (x, _) = intcode::run::until_output(intcode, input)?;
         let x = match x {
             Some(x) => x,
             // game over
             None => {
-                print!("\x1b[{};{}H", 30, 1);
+                if dirty {
+                    vis.present()?;
+                    frames.push(|| screen(&tiles))?;
+                }
                 return Ok(GameState { tiles, score });
             }
         };
 
-        let y =
-            intcode::run::until_output(intcode, input)?.ok_or("malfunctioning intcode program")?;
-        let kind =
-            intcode::run::until_output(intcode, input)?.ok_or("malfunctioning intcode program")?;
+        let y = intcode::run::until_output(intcode, input)?
+            .0
+            .with_context(|| format!("game halted after x={}", x))?;
+        let kind = intcode::run::until_output(intcode, input)?
+            .0
+            .with_context(|| format!("game halted after x={}, y={}", x, y))?;
 
-        if (x, y) == (-1, 0) {
+        // a game tick ends once the ball moves (or the score changes), so only
+        // those are worth a frame, rather than every tile drawn along the way
+        let tick = if (x, y) == (-1, 0) {
             score = kind;
-
-            // move cursor
-            print!("\x1b[{};{}H", 1, 1);
-            print!("Score: {:<10}", score);
+            vis.draw(0, 0, &format!("Score: {:<10}", score))?;
+            true
         } else {
-            tiles.insert((x, y), kind);
+            tiles.insert(Point::new(x as i32, y as i32), kind);
 
             // AI
             match kind {
@@ -62,35 +87,33 @@ fn run_game(intcode: &mut Intcode, delay: u64) -> DynResult<GameState> {
                 _ => {}
             }
 
-            // move cursor
-            print!("\x1b[{};{}H", 1 + y + 1, 1 + x * 3);
-            print!(
-                "{}",
-                match kind {
-                    0 => "   ".to_string(),
-                    1 => "...".to_string(),
-                    2 => {
-                        let color = format!("\x1b[0;{}m", 41 + y % 7);
-                        format!("{}   {}", color, "\x1b[0m")
-                    }
-                    3 => "<=>".to_string(),
-                    4 => " 😂".to_string(),
-                    _ => return Err("malfunctioning intcode program".into()),
+            let tile = match kind {
+                0 => "   ".to_string(),
+                1 => "...".to_string(),
+                2 => {
+                    let color = format!("\x1b[0;{}m", 41 + y % 7);
+                    format!("{}   {}", color, "\x1b[0m")
                 }
-            );
-            print!("\x1b[1;1H");
-        }
-
-        std::io::stdout().flush()?;
+                3 => "<=>".to_string(),
+                4 => " 😂".to_string(),
+                _ => return Err(format!("unknown tile {} at ({}, {})", kind, x, y).into()),
+            };
+            vis.draw(x as usize * 3, y as usize + 1, &tile)?;
+            kind == 4
+        };
 
-        std::thread::sleep(std::time::Duration::from_millis(delay));
+        dirty = !tick;
+        if tick {
+            vis.present()?;
+            frames.push(|| screen(&tiles))?;
+        }
     }
 }
 
 pub fn q1(input: String, _args: &[String]) -> DynResult<usize> {
     let intcode = &mut Intcode::new(input)?;
 
-    let ans = run_game(intcode, 0)?
+    let ans = run_game(intcode, &mut Vis::off(), &mut Frames::new("13-1"))?
         .tiles
         .iter()
         .filter(|(_, kind)| **kind == 2)
@@ -99,15 +122,28 @@ pub fn q1(input: String, _args: &[String]) -> DynResult<usize> {
     Ok(ans)
 }
 
+// superseded by the global `--fps` flag, but kept around for old scripts (a
+// non-zero delay overrides `--fps`)
+pub const Q2_OPTS: &[Opt] = &[Opt::uint(
+    "delay",
+    "0",
+    "(deprecated, use --fps) minimum milliseconds between frames",
+)];
+
 pub fn q2(input: String, args: &[String]) -> DynResult<isize> {
-    let delay = match args.get(0) {
-        None => 0,
-        Some(v) => v.parse::<u64>()?,
-    };
+    let delay = Args::parse(Q2_OPTS, args)?.get::<u64>("delay")?;
 
     let intcode = &mut Intcode::new(input)?;
     intcode.mem().write(0, 2);
 
-    let final_score = run_game(intcode, delay)?.score;
+    let mut vis = match delay {
+        0 => Vis::new(),
+        ms => Vis::with_frame_interval(Duration::from_millis(ms)),
+    };
+    let state = run_game(intcode, &mut vis, &mut Frames::new("13-2"))?;
+    vis.finish()?;
+
+    export::image("13-2", || screen(&state.tiles))?;
+    let final_score = state.score;
     Ok(final_score)
 }
@@ -1,100 +1,101 @@
 use crate::prelude::*;
 
-macro_rules! munge_input {
-    ($input:ident) => {{
-        let input = &$input;
+type Reactions = HashMap<String, (usize, Vec<(usize, String)>)>;
 
-        const E: &str = "invalid input";
+fn parse(input: &str) -> parse::Result<Reactions> {
+    let mut rxns = HashMap::new();
 
-        let mut rxns = HashMap::new();
+    for ln in parse::Span::new(input).lines() {
+        let (ls, rs) = ln.split_once("=>")?;
+        let (product_n, product) = parse::quantity(rs)?;
 
-        for ln in input.split('\n') {
-            let mut react = ln.split("=>");
-            let ls = react.next().ok_or(E)?.trim().split(", ");
-            let mut rs = react.next().ok_or(E)?.trim().split(' ');
+        let reactants = ls
+            .split(",")
+            .map(|r| {
+                let (n, name) = parse::quantity(r)?;
+                Ok((n, name.to_string()))
+            })
+            .collect::<parse::Result<_>>()?;
 
-            let product_n = rs.next().ok_or(E)?.trim().parse::<usize>()?;
-            let product = rs.next().ok_or(E)?.trim().to_string();
-
-            rxns.insert(product.clone(), (product_n, Vec::new()));
-            let reactants = &mut rxns.get_mut(&product).unwrap().1;
+        rxns.insert(product.to_string(), (product_n, reactants));
+    }
 
-            for reactant in ls {
-                let mut reactant = reactant.split(' ');
-                let reactant_n = reactant.next().ok_or(E)?.trim().parse::<usize>()?;
-                let reactant = reactant.next().ok_or(E)?.trim().to_string();
-                reactants.push((reactant_n, reactant));
-            }
-        }
+    Ok(rxns)
+}
 
-        rxns
-    }};
+/// Order the chemicals such that each one comes before it's reactants.
+fn order(rxns: &Reactions) -> DynResult<Vec<&str>> {
+    search::toposort(vec!["FUEL"], |product| match rxns.get(*product) {
+        Some((_, reactants)) => reactants.iter().map(|(_, r)| r.as_str()).collect(),
+        None => Vec::new(),
+    })
 }
 
 fn req_ore<'a>(
-    rxns: &'a HashMap<String, (usize, Vec<(usize, String)>)>,
+    rxns: &'a Reactions,
+    order: &[&'a str],
     excess: &mut HashMap<&'a str, usize>,
     fuel: usize,
 ) -> DynResult<usize> {
-    let mut reqs: VecDeque<(usize, &str)> = VecDeque::new();
-
-    let mut ore = 0;
+    let mut reqs: HashMap<&str, usize> = HashMap::new();
+    reqs.insert("FUEL", fuel);
 
-    reqs.push_back((fuel, "FUEL"));
-    while let Some((mut req_n, product)) = reqs.pop_front() {
-        if product == "ORE" {
-            ore += req_n;
+    // every product's total requirement is known by the time it's reached
+    for &product in order {
+        let mut req_n = reqs.get(product).copied().unwrap_or(0);
+        if product == "ORE" || req_n == 0 {
             continue;
         }
 
-        if let Some(excess) = excess.get_mut(&product) {
-            if *excess >= req_n {
-                *excess -= req_n;
-                continue;
-            } else {
-                req_n -= *excess;
-                *excess = 0;
-            }
-        }
+        let excess = excess.entry(product).or_default();
+        let used = req_n.min(*excess);
+        *excess -= used;
+        req_n -= used;
 
-        let (multiplier, reactants) = rxns.get(product).expect("broken invariant");
-        let iters = (req_n as f32 / *multiplier as f32).ceil() as usize;
+        let (multiplier, reactants) = rxns
+            .get(product)
+            .ok_or_else(|| format!("no reaction produces {}", product))?;
+        let iters = req_n.div_ceil(*multiplier);
 
-        *excess.entry(product).or_default() += (iters * multiplier) - req_n;
+        *excess += (iters * multiplier) - req_n;
 
         for (reactant_n, reactant) in reactants {
-            reqs.push_back((reactant_n * iters, reactant))
+            *reqs.entry(reactant).or_default() += reactant_n * iters;
         }
     }
 
-    Ok(ore)
+    Ok(reqs.get("ORE").copied().unwrap_or(0))
 }
 
 pub fn q1(input: String, _args: &[String]) -> DynResult<usize> {
-    let rxns = munge_input!(input);
+    let rxns = parse(&input)?;
+    let order = order(&rxns)?;
 
     let mut excess = HashMap::new();
 
-    req_ore(&rxns, &mut excess, 1)
+    req_ore(&rxns, &order, &mut excess, 1)
 }
 
 pub fn q2(input: String, _args: &[String]) -> DynResult<usize> {
-    let rxns = munge_input!(input);
+    let rxns = parse(&input)?;
+    let order = order(&rxns)?;
 
     let mut excess = HashMap::new();
 
-    // must be careful with the step size. too large, and it causes a "subtract with
-    // overflow" error on line 63.
+    // make as much fuel at a time as possible, halving the batch size whenever
+    // there isn't enough ore left for a full batch
     let mut step_size = 2usize.pow(10);
     let mut ore = 1000000000000;
     let mut fuel = 0;
     loop {
-        let req_ore = req_ore(&rxns, &mut excess, step_size)?;
+        let prev_excess = excess.clone();
+        let req_ore = req_ore(&rxns, &order, &mut excess, step_size)?;
         if req_ore > ore {
             if step_size == 1 {
                 break;
             }
 
+            excess = prev_excess;
             step_size /= 2;
             continue;
         }
@@ -2,22 +2,16 @@ use crate::prelude::*;
 
 use std::iter;
 
-macro_rules! munge_input {
-    ($input:ident) => {{
-        let input = &$input;
-        input
-            .chars()
-            .map(|x| x.to_digit(10).map(|x| x as i32))
-            .collect::<Option<Vec<_>>>()
-            .ok_or("invalid input")?
-    }};
+fn parse(input: &str) -> parse::Result<Vec<i32>> {
+    let digits = parse::digits(parse::Span::new(input))?;
+    Ok(digits.into_iter().map(|d| d as i32).collect())
 }
 
 fn pattern(i: usize) -> impl Iterator<Item = i32> {
-    (iter::repeat(0).take(i))
-        .chain(iter::repeat(1).take(i))
-        .chain(iter::repeat(0).take(i))
-        .chain(iter::repeat(-1).take(i))
+    iter::repeat_n(0, i)
+        .chain(iter::repeat_n(1, i))
+        .chain(iter::repeat_n(0, i))
+        .chain(iter::repeat_n(-1, i))
         .cycle()
         .skip(1)
 }
@@ -36,7 +30,7 @@ fn fft(input: Vec<i32>) -> Vec<i32> {
 }
 
 pub fn q1(input: String, _args: &[String]) -> DynResult<String> {
-    let mut input = munge_input!(input);
+    let mut input = parse(&input)?;
 
     for _ in 0..100 {
         input = fft(input);
@@ -52,8 +46,10 @@ pub fn q1(input: String, _args: &[String]) -> DynResult<String> {
     Ok(ans)
 }
 
+pub const Q2_SLOW: &str = "the 10000x fft never finishes";
+
 pub fn q2(input: String, _args: &[String]) -> DynResult<String> {
-    let input = munge_input!(input);
+    let input = parse(&input)?;
     let in_len = input.len();
 
     let input = input
@@ -1,105 +1,55 @@
 use crate::prelude::*;
 
-macro_rules! munge_input {
-    ($input:ident) => {{
-        let input = &$input;
-
-        let mut wires = input
-            .split('\n')
-            .map(|s| {
-                s.split(',')
-                    .map(|s| s.parse::<PathChunk>())
-                    .collect::<Result<Vec<_>, _>>()
-            })
-            .collect::<std::result::Result<Vec<_>, _>>()
-            .map_err(|_| "failed to parse input")?;
-
-        let w2 = wires.pop().ok_or_else(|| "second wire path unspecified")?;
-        let w1 = wires.pop().ok_or_else(|| "first wire path unspecified")?;
-
-        if !wires.is_empty() {
-            return Err("specified more than 2 wires".into());
-        }
-
-        (w1, w2)
-    }};
-}
-
 struct PathChunk {
     pub dir: Dir,
     pub dist: usize,
 }
 
-#[derive(Clone, Copy)]
-enum Dir {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-impl Dir {
-    fn to_delta(self) -> (i32, i32) {
-        use self::Dir::*;
-        match self {
-            Up => (0, -1),
-            Down => (0, 1),
-            Left => (-1, 0),
-            Right => (1, 0),
-        }
-    }
-}
-
-impl std::str::FromStr for Dir {
-    type Err = ();
-    fn from_str(s: &str) -> Result<Dir, ()> {
-        use self::Dir::*;
-        let res = match s {
-            "U" => Up,
-            "D" => Down,
-            "L" => Left,
-            "R" => Right,
-            _ => return Err(()),
-        };
-        Ok(res)
-    }
-}
+fn parse(input: &str) -> parse::Result<(Vec<PathChunk>, Vec<PathChunk>)> {
+    let mut wires = parse::lines(input, |ln| {
+        ln.split(",")
+            .map(|chunk| {
+                let (dir, dist) = chunk.trim().split_at(1)?;
+                Ok(PathChunk {
+                    dir: dir.parse()?,
+                    dist: dist.parse()?,
+                })
+            })
+            .collect()
+    })?;
 
-impl std::str::FromStr for PathChunk {
-    type Err = ();
-    fn from_str(s: &str) -> Result<PathChunk, ()> {
-        Ok(PathChunk {
-            dir: s[..1].parse()?,
-            dist: s[1..].parse().map_err(drop)?,
-        })
+    if wires.len() != 2 {
+        let msg = format!("expected 2 wire paths, found {}", wires.len());
+        return Err(parse::Span::new(input).error(msg));
     }
+    let w2 = wires.pop().unwrap();
+    let w1 = wires.pop().unwrap();
+    Ok((w1, w2))
 }
 
 pub fn q1(input: String, _args: &[String]) -> DynResult<usize> {
-    let (w1, w2) = munge_input!(input);
+    let (w1, w2) = parse(&input)?;
 
     // let's just brute force it wheeee
 
-    let mut w1_points: HashSet<(i32, i32)> = HashSet::new();
+    let mut w1_points: HashSet<Point> = HashSet::new();
 
-    let mut cur_p = (0, 0);
+    let mut cur_p = Point::ORIGIN;
     for PathChunk { dir, dist } in w1 {
-        let (dx, dy) = dir.to_delta();
         for _ in 0..dist {
-            cur_p = (cur_p.0 + dx, cur_p.1 + dy);
-            w1_points.insert(cur_p.clone());
+            cur_p += dir;
+            w1_points.insert(cur_p);
         }
     }
 
     let mut min_dist = None;
-    let mut cur_p = (0, 0);
+    let mut cur_p = Point::ORIGIN;
     for PathChunk { dir, dist } in w2 {
-        let (dx, dy) = dir.to_delta();
         for _ in 0..dist {
-            cur_p = (cur_p.0 + dx, cur_p.1 + dy);
+            cur_p += dir;
 
             if w1_points.contains(&cur_p) {
-                let cur_dist = cur_p.0.abs() + cur_p.1.abs();
+                let cur_dist = cur_p.manhattan();
                 min_dist = match min_dist {
                     None => Some(cur_dist),
                     Some(min_dist) => Some(std::cmp::min(min_dist, cur_dist)),
@@ -115,17 +65,16 @@ pub fn q1(input: String, _args: &[String]) -> DynResult<usize> {
 }
 
 pub fn q2(input: String, _args: &[String]) -> DynResult<usize> {
-    let (w1, w2) = munge_input!(input);
+    let (w1, w2) = parse(&input)?;
 
     // ((x, y), step)
-    let mut w1_points: HashMap<(i32, i32), usize> = HashMap::new();
+    let mut w1_points: HashMap<Point, usize> = HashMap::new();
 
-    let mut cur_p = (0, 0);
+    let mut cur_p = Point::ORIGIN;
     let mut cur_step = 0;
     for PathChunk { dir, dist } in w1 {
-        let (dx, dy) = dir.to_delta();
         for _ in 0..dist {
-            cur_p = (cur_p.0 + dx, cur_p.1 + dy);
+            cur_p += dir;
             cur_step += 1;
             w1_points.insert(cur_p, cur_step);
         }
@@ -133,12 +82,11 @@ pub fn q2(input: String, _args: &[String]) -> DynResult<usize> {
 
     let mut min_steps = None;
 
-    let mut cur_p = (0, 0);
+    let mut cur_p = Point::ORIGIN;
     let mut cur_step = 0;
     for PathChunk { dir, dist } in w2 {
-        let (dx, dy) = dir.to_delta();
         for _ in 0..dist {
-            cur_p = (cur_p.0 + dx, cur_p.1 + dy);
+            cur_p += dir;
             cur_step += 1;
 
             if let Some(w1_step) = w1_points.get(&cur_p) {
@@ -1,13 +1,8 @@
 use crate::prelude::*;
 
-macro_rules! munge_input {
-    ($input:ident) => {{
-        let input = &$input;
-        let mut input = input.split('-');
-        let start = input.next().unwrap().parse::<usize>()?;
-        let end = input.next().unwrap().parse::<usize>()?;
-        (start, end)
-    }};
+fn parse(input: &str) -> parse::Result<(usize, usize)> {
+    let (start, end) = parse::Span::new(input).split_once("-")?;
+    Ok((start.parse()?, end.parse()?))
 }
 
 fn digits(val: usize) -> Vec<char> {
@@ -38,25 +33,25 @@ fn has_run2(digits: &[char]) -> bool {
 }
 
 pub fn q1(input: String, _args: &[String]) -> DynResult<usize> {
-    let (start, end) = munge_input!(input);
+    let (start, end) = parse(&input)?;
 
     let ans = (start..=end)
         .map(digits)
-        .filter(|d| has_pair(&d))
-        .filter(|d| ascending(&d))
+        .filter(|d| has_pair(d))
+        .filter(|d| ascending(d))
         .count();
 
     Ok(ans)
 }
 
 pub fn q2(input: String, _args: &[String]) -> DynResult<usize> {
-    let (start, end) = munge_input!(input);
+    let (start, end) = parse(&input)?;
 
     let ans = (start..=end)
         .map(digits)
-        .filter(|d| has_pair(&d))
-        .filter(|d| ascending(&d))
-        .filter(|d| has_run2(&d))
+        .filter(|d| has_pair(d))
+        .filter(|d| ascending(d))
+        .filter(|d| has_run2(d))
         .count();
 
     Ok(ans)
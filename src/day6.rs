@@ -1,24 +1,23 @@
 use crate::prelude::*;
 
-macro_rules! munge_input {
-    ($input:ident) => {{
-        let input = &$input;
-        let pairs = input
-            .split('\n')
-            .map(|s| {
-                let mut c = s.splitn(2, ')');
-                (c.next().unwrap(), c.next().unwrap_or(""))
-            })
-            .collect::<Vec<_>>();
-        if pairs.iter().any(|(a, _)| a.is_empty()) {
-            return Err("Malformed input".into());
+fn parse(input: &str) -> parse::Result<Vec<(&str, &str)>> {
+    parse::lines(input, |ln| {
+        let (a, b) = ln.split_once(")")?;
+        if a.trim().is_empty() {
+            return Err(a.error("expected a planet name"));
         }
-        pairs
-    }};
+        Ok((a.as_str(), b.as_str()))
+    })
 }
 
+pub const Q1_OPTS: &[Opt] = &[Opt::flag(
+    "recursive",
+    "compute the checksum recursively, instead of with an explicit stack",
+)];
+
 pub fn q1(input: String, args: &[String]) -> DynResult<usize> {
-    let input = munge_input!(input);
+    let args = Args::parse(Q1_OPTS, args)?;
+    let input = parse(&input)?;
 
     let mut orbits: HashMap<&str, HashSet<&str>> = HashMap::new();
     for (a, b) in input {
@@ -26,10 +25,9 @@ pub fn q1(input: String, args: &[String]) -> DynResult<usize> {
         orbits.entry(b).or_default();
     }
 
-    let checksum = match args.get(0).map(|s| s.as_str()) {
-        Some("recursive") => checksum(&orbits, "COM", 0),
-        Some(_) => return Err("invalid argument. did you mistype `recrusive`?".into()),
-        None => {
+    let checksum = match args.flag("recursive") {
+        true => checksum(&orbits, "COM", 0),
+        false => {
             let mut s = Vec::new();
             s.push(("COM", 0));
 
@@ -53,7 +51,7 @@ fn checksum(orbits: &HashMap<&str, HashSet<&str>>, root: &str, d: usize) -> usiz
 }
 
 pub fn q2(input: String, _args: &[String]) -> DynResult<usize> {
-    let input = munge_input!(input);
+    let input = parse(&input)?;
 
     let mut orbits: HashMap<&str, HashSet<&str>> = HashMap::new();
     for (a, b) in input {
@@ -61,21 +59,9 @@ pub fn q2(input: String, _args: &[String]) -> DynResult<usize> {
         orbits.entry(b).or_default().insert(a);
     }
 
-    // basic BFS
-    let mut q = VecDeque::new();
-    q.push_back(("YOU", "", 0));
-    while let Some((planet, parent, hops)) = q.pop_front() {
-        if planet == "SAN" {
-            return Ok(hops - 2); // YOU and SAN don't count
-        }
-
-        q.extend(
-            orbits[planet]
-                .iter()
-                .filter(|p| **p != parent)
-                .map(|p| (*p, planet, hops + 1)),
-        );
-    }
+    let path = search::bfs("YOU", |planet| orbits[planet].iter().copied(), |&p| p == "SAN")
+        .ok_or("Couldn't find route from YOU to SAN")?;
 
-    Err("Couldn't find route from YOU to SAN".into())
+    // YOU and SAN don't count
+    Ok(path.len() - 3)
 }
@@ -8,7 +8,7 @@ pub fn q1(input: String, _args: &[String]) -> DynResult<(isize, Vec<isize>)> {
     let mut input = Vec::new();
     let mut output = Vec::new();
 
-    let mut max_out = (std::isize::MIN, Vec::new());
+    let mut max_out = (isize::MIN, Vec::new());
 
     for phases in (0..5).permutations(5) {
         let mut prev_out = 0;
@@ -39,13 +39,18 @@ pub fn q1(input: String, _args: &[String]) -> DynResult<(isize, Vec<isize>)> {
     Ok(max_out)
 }
 
+pub const Q2_OPTS: &[Opt] = &[
+    #[cfg(feature = "extras")]
+    Opt::flag("threaded", "run each amplifier on it's own thread"),
+];
+
 pub fn q2(input: String, args: &[String]) -> DynResult<(isize, Vec<isize>)> {
-    #[allow(clippy::single_match)]
-    match args.get(0).map(|x| x.as_str()) {
+    let args = Args::parse(Q2_OPTS, args)?;
+
+    // only a valid option when the "extras" feature is enabled
+    if args.flag("threaded") {
         #[cfg(feature = "extras")]
-        Some("threaded") => return q2_threaded::q2(input, &[]),
-        Some(_) => return Err("invalid argument".into()),
-        None => {}
+        return q2_threaded::q2(input, &[]);
     }
 
     let base_intcode = Intcode::new(input)?;
@@ -53,7 +58,7 @@ pub fn q2(input: String, args: &[String]) -> DynResult<(isize, Vec<isize>)> {
         .map(|_| (base_intcode.clone(), VecDeque::new()))
         .collect::<Vec<_>>();
 
-    let mut max_out = (std::isize::MIN, Vec::new());
+    let mut max_out = (isize::MIN, Vec::new());
 
     for phases in (5..10).permutations(5) {
         // seed the amps with their phase
@@ -68,7 +73,7 @@ pub fn q2(input: String, args: &[String]) -> DynResult<(isize, Vec<isize>)> {
         'outer: loop {
             for (amp, input) in &mut amps {
                 input.push_back(out);
-                match intcode::run::until_output(amp, input)? {
+                match intcode::run::until_output(amp, input)?.0 {
                     Some(output) => out = output,
                     None => {
                         // it didn't need the last input
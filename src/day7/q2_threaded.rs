@@ -126,7 +126,7 @@ pub fn q2(input: String, _args: &[String]) -> DynResult<(isize, Vec<isize>)> {
     }
 
     // Start the tests
-    let mut max_out = (std::isize::MIN, Vec::new());
+    let mut max_out = (isize::MIN, Vec::new());
     for phases in (5..10).permutations(5) {
         // seed the amps with their phase
         for (c, &phase) in cmd_chans.iter().zip(phases.iter()) {
@@ -1,56 +1,43 @@
 use crate::prelude::*;
 
-macro_rules! munge_input {
-    ($input:ident) => {{
-        let input = &$input;
-        input
-            .chars()
-            .collect::<Vec<_>>()
-            .chunks(25 * 6)
-            .map(|s| s.to_vec())
-            .collect::<Vec<_>>()
-    }};
+const WIDTH: usize = 25;
+const HEIGHT: usize = 6;
+
+fn parse(input: &str) -> parse::Result<Vec<Vec<u32>>> {
+    let pixels = parse::digits(parse::Span::new(input))?;
+    Ok(pixels.chunks(WIDTH * HEIGHT).map(|l| l.to_vec()).collect())
 }
 
 pub fn q1(input: String, _args: &[String]) -> DynResult<usize> {
-    let layers = munge_input!(input);
+    let layers = parse(&input)?;
 
-    let count_char = |c| move |l: &Vec<char>| l.iter().filter(|x| **x == c).count();
+    let count = |d| move |l: &Vec<u32>| l.iter().filter(|&&x| x == d).count();
 
-    let l = layers.into_iter().min_by_key(count_char('0')).unwrap();
+    let l = layers.into_iter().min_by_key(count(0)).unwrap();
 
-    Ok(count_char('1')(&l) * count_char('2')(&l))
+    Ok(count(1)(&l) * count(2)(&l))
 }
 
-pub fn q2(input: String, _args: &[String]) -> DynResult<()> {
-    let layers = munge_input!(input);
+pub fn q2(input: String, _args: &[String]) -> DynResult<Image> {
+    let layers = parse(&input)?;
 
-    let ans = layers
+    let pixels = layers
         .into_iter()
-        .fold(vec![' '; 25 * 6], |mut i, l| {
+        .fold(vec![2; WIDTH * HEIGHT], |mut i, l| {
             for (i, l) in i.iter_mut().zip(l) {
-                match *i {
-                    ' ' | '2' => *i = l,
-                    _ => {}
+                if *i == 2 {
+                    *i = l;
                 }
             }
             i
         })
-        // shuffle the chars around (for readability)
         .into_iter()
-        .map(|c| match c {
-            '0' => " ",
-            '1' => "X",
-            _ => "_",
-        })
-        .collect::<Vec<_>>()
-        // chunk into lines and re-join to get the image
-        .chunks(25)
-        .map(|c| c.join(""))
-        .collect::<Vec<_>>()
-        .join("\n");
-
-    println!("{}", ans);
-
-    Ok(())
+        .map(|c| c == 1)
+        .collect();
+
+    let image = Image::new(WIDTH, pixels)?;
+    export::image("8-2", || {
+        Raster::from_image(&image, export::WHITE, export::BLACK)
+    })?;
+    Ok(image)
 }
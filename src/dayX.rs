@@ -12,22 +12,21 @@ use crate::prelude::*;
 //     let intcode =  &mut Intcode::new(input)?;
 //     let input = &mut VecDeque::new();
 //     input.push_back(0);
-//     while let Some(output) = intcode::run::until_output(intcode, input)? {
+//     while let (Some(output), _) = intcode::run::until_output(intcode, input)? {
 //         eprintln!("{:?}", output);
 //         // ...
 //     }
 //     Ok(())
 // }
 
-macro_rules! munge_input {
-    ($input:ident) => {{
-        let input = &$input;
-        input.split('\n')
-    }};
+// pub const Q1_OPTS: &[Opt] = &[Opt::flag("vis", "visualize the solution")];
+
+fn parse(input: &str) -> parse::Result<Vec<&str>> {
+    parse::lines(input, |ln| Ok(ln.as_str()))
 }
 
 pub fn q1(input: String, _args: &[String]) -> DynResult<()> {
-    let input = munge_input!(input);
+    let input = parse(&input)?;
 
     let _ = input;
 
@@ -35,21 +34,12 @@ pub fn q1(input: String, _args: &[String]) -> DynResult<()> {
 }
 
 pub fn q2(input: String, _args: &[String]) -> DynResult<()> {
-    let input = munge_input!(input);
+    let input = parse(&input)?;
 
     let _ = input;
 
     Ok(())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn q1_e1() {
-        let input = "...";
-        let output = q1(input.to_string(), &[]);
-        assert_eq!(output.unwrap(), ());
-    }
-}
+// Examples go in `examples/<day>/<name>.txt`, with their expected answers in
+// `examples/<day>/<name>.answers` (see `harness::examples`).
@@ -0,0 +1,222 @@
+//! Errors which remember what was going on when they happened.
+//!
+//! Solutions still return [`DynResult`], so any error can be bubbled up with
+//! `?`. The [`Context`] trait wraps an error in an [`Error`] describing the
+//! step which failed (e.g: "while exploring at (3, -4)"), and the harness
+//! prints the whole chain with [`Report`].
+//!
+//! [`DynResult`]: crate::DynResult
+
+use std::error::Error as StdError;
+use std::fmt::{self, Display};
+
+use crate::parse;
+
+#[derive(Debug)]
+pub enum Error {
+    /// An intcode program failed to run
+    Intcode(intcode::Error),
+    /// The input couldn't be parsed
+    Parse(parse::Error),
+    /// Anything else (usually a plain message)
+    Other(Box<dyn StdError>),
+    /// What was happening when `source` occurred
+    Context { context: String, source: Box<Error> },
+}
+
+impl Error {
+    pub fn msg(msg: impl Display) -> Error {
+        Error::Other(msg.to_string().into())
+    }
+
+    /// Wrap the error with some context.
+    pub fn context(self, context: impl Display) -> Error {
+        Error::Context {
+            context: context.to_string(),
+            source: Box::new(self),
+        }
+    }
+
+    /// The original error, without any context.
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::Context { source, .. } => source.root(),
+            e => e,
+        }
+    }
+
+    /// The original error, if it came from an intcode program.
+    pub fn intcode(&self) -> Option<&intcode::Error> {
+        match self.root() {
+            Error::Intcode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Intcode(e) => write!(f, "{}", e),
+            Error::Parse(e) => write!(f, "{}", e),
+            Error::Other(e) => write!(f, "{}", e),
+            Error::Context { context, .. } => write!(f, "{}", context),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Intcode(e) => e.source(),
+            Error::Parse(e) => e.source(),
+            Error::Other(e) => e.source(),
+            Error::Context { source, .. } => Some(&**source),
+        }
+    }
+}
+
+impl From<intcode::Error> for Error {
+    fn from(e: intcode::Error) -> Error {
+        Error::Intcode(e)
+    }
+}
+
+impl From<parse::Error> for Error {
+    fn from(e: parse::Error) -> Error {
+        Error::Parse(e)
+    }
+}
+
+impl From<&str> for Error {
+    fn from(msg: &str) -> Error {
+        Error::msg(msg)
+    }
+}
+
+impl From<String> for Error {
+    fn from(msg: String) -> Error {
+        Error::msg(msg)
+    }
+}
+
+/// Errors which have been bubbled up as a `Box<dyn Error>` are unboxed back
+/// into their original variant (if they have one).
+impl From<Box<dyn StdError>> for Error {
+    fn from(e: Box<dyn StdError>) -> Error {
+        let e = match e.downcast::<Error>() {
+            Ok(e) => return *e,
+            Err(e) => e,
+        };
+        let e = match e.downcast::<intcode::Error>() {
+            Ok(e) => return Error::Intcode(*e),
+            Err(e) => e,
+        };
+        match e.downcast::<parse::Error>() {
+            Ok(e) => Error::Parse(*e),
+            Err(e) => Error::Other(e),
+        }
+    }
+}
+
+/// Attach context to failed `Result`s, or missing `Option`s.
+pub trait Context<T> {
+    fn context(self, context: impl Display) -> Result<T, Error>;
+
+    /// Like [`Context::context`], but only builds the context on failure.
+    fn with_context<C: Display>(self, f: impl FnOnce() -> C) -> Result<T, Error>;
+}
+
+impl<T, E: Into<Box<dyn StdError>>> Context<T> for Result<T, E> {
+    fn context(self, context: impl Display) -> Result<T, Error> {
+        self.map_err(|e| Error::from(e.into()).context(context))
+    }
+
+    fn with_context<C: Display>(self, f: impl FnOnce() -> C) -> Result<T, Error> {
+        self.map_err(|e| Error::from(e.into()).context(f()))
+    }
+}
+
+/// For `Option`s, the context becomes the error message.
+impl<T> Context<T> for Option<T> {
+    fn context(self, context: impl Display) -> Result<T, Error> {
+        self.ok_or_else(|| Error::msg(context))
+    }
+
+    fn with_context<C: Display>(self, f: impl FnOnce() -> C) -> Result<T, Error> {
+        self.ok_or_else(|| Error::msg(f()))
+    }
+}
+
+/// Displays an error along with it's chain of sources: on one line by default
+/// (e.g: `while painting at (1, 2): Encountered unknown opcode: 99`), or with
+/// each source on it's own line when using `{:#}`.
+pub struct Report<'a>(pub &'a (dyn StdError + 'static));
+
+impl Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)?;
+        let mut source = self.0.source();
+        while let Some(e) = source {
+            match f.alternate() {
+                true => write!(f, "\n  caused by: {}", e)?,
+                false => write!(f, ": {}", e)?,
+            }
+            source = e.source();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::DynResult;
+
+    fn run() -> DynResult<()> {
+        Err(intcode::Error::InvalidOpcode(42))?
+    }
+
+    fn step(pos: (i32, i32)) -> DynResult<()> {
+        run().with_context(|| format!("while exploring at {:?}", pos))?;
+        Ok(())
+    }
+
+    fn search() -> DynResult<()> {
+        step((3, -4)).context("while searching")?;
+        Ok(())
+    }
+
+    #[test]
+    fn chains() {
+        let e = Error::from(search().unwrap_err());
+        assert_eq!(e.to_string(), "while searching");
+        assert!(matches!(
+            e.intcode(),
+            Some(intcode::Error::InvalidOpcode(42))
+        ));
+        assert_eq!(
+            Report(&e).to_string(),
+            "while searching: while exploring at (3, -4): Encountered unknown opcode: 42"
+        );
+        assert_eq!(
+            format!("{:#}", Report(&e)),
+            "while searching\n  caused by: while exploring at (3, -4)\n  caused by: \
+             Encountered unknown opcode: 42"
+        );
+    }
+
+    #[test]
+    fn variants() {
+        let e = parse::Span::new("x").parse::<u8>().context("while parsing");
+        assert!(matches!(e.unwrap_err().root(), Error::Parse(_)));
+
+        let e = Error::from(Box::<dyn StdError>::from("bruh"));
+        assert!(matches!(e, Error::Other(_)));
+        assert_eq!(e.intcode().map(|_| ()), None);
+
+        let e = None::<u8>.context("nothing here").unwrap_err();
+        assert_eq!(Report(&e).to_string(), "nothing here");
+    }
+}
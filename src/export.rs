@@ -0,0 +1,524 @@
+//! Exporting grids and visualizations as image files.
+//!
+//! Both PPM and PNG are written without any external dependencies (PNGs are
+//! simply left uncompressed). Days export images through [`image`] and
+//! [`Frames`], which only do anything when an export directory was set with
+//! the `--export` flag (see [`extract_config`]).
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::grid::{Grid, Point, SparseGrid};
+use crate::{DynResult, Image};
+
+/// An RGB color.
+pub type Rgb = [u8; 3];
+
+pub const BLACK: Rgb = [0, 0, 0];
+pub const WHITE: Rgb = [255, 255, 255];
+
+/// Convert a color from the 256 color ANSI palette (as used by
+/// [`Grid::render_colors`]) into RGB.
+pub fn ansi_256(color: u8) -> Rgb {
+    const BASIC: [Rgb; 16] = [
+        [0, 0, 0],
+        [128, 0, 0],
+        [0, 128, 0],
+        [128, 128, 0],
+        [0, 0, 128],
+        [128, 0, 128],
+        [0, 128, 128],
+        [192, 192, 192],
+        [128, 128, 128],
+        [255, 0, 0],
+        [0, 255, 0],
+        [255, 255, 0],
+        [0, 0, 255],
+        [255, 0, 255],
+        [0, 255, 255],
+        [255, 255, 255],
+    ];
+
+    match color {
+        0..=15 => BASIC[color as usize],
+        // 6x6x6 color cube
+        16..=231 => {
+            let level = |c: u8| if c == 0 { 0 } else { 55 + c * 40 };
+            let c = color - 16;
+            [level(c / 36), level(c / 6 % 6), level(c % 6)]
+        }
+        // grayscale ramp
+        _ => {
+            let level = 8 + (color - 232) * 10;
+            [level, level, level]
+        }
+    }
+}
+
+/// File formats images can be exported as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Ppm,
+    Png,
+}
+
+impl Format {
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Ppm => "ppm",
+            Format::Png => "png",
+        }
+    }
+}
+
+/// A grid of RGB pixels.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Raster {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Raster {
+    /// Create a raster by calling `f` with the (x, y) of each pixel.
+    pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(usize, usize) -> Rgb) -> Raster {
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| f(x, y))
+            .collect();
+        Raster {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Convert a grid, using `palette` to pick each cell's color.
+    pub fn from_grid<T>(grid: &Grid<T>, mut palette: impl FnMut(Point, &T) -> Rgb) -> Raster {
+        Raster::from_fn(grid.width(), grid.height(), |x, y| {
+            let p = Point::new(x as i32, y as i32);
+            palette(p, &grid[p])
+        })
+    }
+
+    /// Convert a sparse grid's bounding box, using `palette` to pick each
+    /// cell's color (where empty cells are passed as `None`).
+    pub fn from_sparse<T>(
+        grid: &SparseGrid<T>,
+        mut palette: impl FnMut(Point, Option<&T>) -> Rgb,
+    ) -> Raster {
+        match grid.bounds() {
+            Some(bounds) => Raster::from_fn(bounds.width(), bounds.height(), |x, y| {
+                let p = bounds.min + Point::new(x as i32, y as i32);
+                palette(p, grid.get(p))
+            }),
+            None => Raster::from_fn(0, 0, |_, _| BLACK),
+        }
+    }
+
+    /// Convert a black and white image.
+    pub fn from_image(image: &Image, on: Rgb, off: Rgb) -> Raster {
+        Raster::from_fn(image.width(), image.height(), |x, y| {
+            if image.get(x, y) {
+                on
+            } else {
+                off
+            }
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Whether the raster has no pixels (e.g: it came from an empty grid).
+    pub fn is_empty(&self) -> bool {
+        self.pixels.is_empty()
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Scale the raster up, such that each pixel becomes a `factor` x `factor`
+    /// square.
+    pub fn scale(&self, factor: usize) -> Raster {
+        Raster::from_fn(self.width * factor, self.height * factor, |x, y| {
+            self.get(x / factor, y / factor)
+        })
+    }
+
+    /// Encode as a binary PPM (P6).
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        out.extend(self.pixels.iter().flatten());
+        out
+    }
+
+    /// Encode as an (uncompressed) 8-bit RGB PNG.
+    pub fn to_png(&self) -> Vec<u8> {
+        fn chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+            out.extend(&(data.len() as u32).to_be_bytes());
+            let start = out.len();
+            out.extend(kind);
+            out.extend(data);
+            let crc = crc32(&out[start..]);
+            out.extend(&crc.to_be_bytes());
+        }
+
+        let mut ihdr = Vec::new();
+        ihdr.extend(&(self.width as u32).to_be_bytes());
+        ihdr.extend(&(self.height as u32).to_be_bytes());
+        // 8 bits per channel, RGB, default compression / filtering, no interlacing
+        ihdr.extend(&[8, 2, 0, 0, 0]);
+
+        // each scanline is prefixed by it's filter type (0 = none)
+        let mut raw = Vec::with_capacity((self.width * 3 + 1) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            raw.push(0);
+            raw.extend(row.iter().flatten());
+        }
+
+        let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+        chunk(&mut out, b"IHDR", &ihdr);
+        chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+        chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    pub fn encode(&self, format: Format) -> Vec<u8> {
+        match format {
+            Format::Ppm => self.to_ppm(),
+            Format::Png => self.to_png(),
+        }
+    }
+
+    /// Save the raster, picking the format based on the file's extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> DynResult<()> {
+        let path = path.as_ref();
+        let format = match path.extension().and_then(|e| e.to_str()) {
+            Some("ppm") => Format::Ppm,
+            Some("png") => Format::Png,
+            _ => return Err(format!("Unknown image format: {}", path.display()).into()),
+        };
+        // neither format can represent an image without any pixels
+        if self.is_empty() {
+            return Err(format!("Can't write an empty image to {}", path.display()).into());
+        }
+        std::fs::write(path, self.encode(format))
+            .map_err(|e| format!("Could not write {}: {}", path.display(), e).into())
+    }
+}
+
+/// CRC-32 (as used by PNG).
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in data {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &x in data {
+        a = (a + x as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    (b << 16) | a
+}
+
+/// Wrap `data` in a zlib stream, using uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        // an empty final block
+        out.extend(&[1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend(&len.to_le_bytes());
+        out.extend(&(!len).to_le_bytes());
+        out.extend(block);
+    }
+
+    out.extend(&adler32(data).to_be_bytes());
+    out
+}
+
+/// Where (and how) days export their images.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub dir: PathBuf,
+    pub format: Format,
+    /// Size of each cell, in pixels
+    pub scale: usize,
+    /// Whether animated days should dump every frame
+    pub frames: bool,
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Remove any `--export <dir>`, `--export-format <png|ppm>`, `--scale <n>`,
+/// and `--frames` flags from `args`, enabling exports if a directory was
+/// given. Can only be set once.
+pub fn extract_config(args: &mut Vec<String>) -> DynResult<()> {
+    if let Some(config) = parse_config(args)? {
+        let _ = CONFIG.set(config);
+    }
+    Ok(())
+}
+
+/// Flags which reproduce the current configuration in a worker subprocess.
+pub(crate) fn worker_args() -> Vec<String> {
+    let config = match CONFIG.get() {
+        Some(config) => config,
+        None => return Vec::new(),
+    };
+
+    let mut args = vec![
+        "--export".to_string(),
+        config.dir.display().to_string(),
+        "--export-format".to_string(),
+        config.format.extension().to_string(),
+        "--scale".to_string(),
+        config.scale.to_string(),
+    ];
+    if config.frames {
+        args.push("--frames".to_string());
+    }
+    args
+}
+
+fn parse_config(args: &mut Vec<String>) -> DynResult<Option<Config>> {
+    let mut take = |flag: &str| -> DynResult<Option<String>> {
+        match args.iter().position(|a| a == flag) {
+            Some(pos) => match args.get(pos + 1).cloned() {
+                Some(val) => {
+                    args.drain(pos..pos + 2);
+                    Ok(Some(val))
+                }
+                None => Err(format!("{} requires a value", flag).into()),
+            },
+            None => Ok(None),
+        }
+    };
+
+    let dir = take("--export")?;
+    let format = match take("--export-format")?.as_deref() {
+        None | Some("png") => Format::Png,
+        Some("ppm") => Format::Ppm,
+        Some(other) => return Err(format!("Unknown image format: {}", other).into()),
+    };
+    let scale = match take("--scale")?.map(|s| s.parse::<usize>()) {
+        None => 4,
+        Some(Ok(scale)) if scale > 0 => scale,
+        Some(_) => return Err("--scale requires a positive number".into()),
+    };
+    let frames = match args.iter().position(|a| a == "--frames") {
+        Some(pos) => {
+            args.remove(pos);
+            true
+        }
+        None => false,
+    };
+
+    match dir {
+        Some(dir) => Ok(Some(Config {
+            dir: dir.into(),
+            format,
+            scale,
+            frames,
+        })),
+        None if frames => Err("--frames requires an --export directory".into()),
+        None => Ok(None),
+    }
+}
+
+/// Export a still image named `name` (e.g: `8-2`), if exports are enabled.
+/// `raster` isn't called otherwise, and empty images are skipped.
+pub fn image(name: &str, raster: impl FnOnce() -> Raster) -> DynResult<()> {
+    let config = match CONFIG.get() {
+        Some(config) => config,
+        None => return Ok(()),
+    };
+
+    let raster = raster();
+    if raster.is_empty() {
+        return Ok(());
+    }
+    std::fs::create_dir_all(&config.dir)?;
+    let path = config
+        .dir
+        .join(format!("{}.{}", name, config.format.extension()));
+    raster.scale(config.scale).save(path)
+}
+
+/// A numbered sequence of frames, written to a directory.
+pub struct Frames {
+    config: Option<Config>,
+    dir: PathBuf,
+    count: usize,
+}
+
+impl Frames {
+    /// Dump frames to a directory named `name` (e.g: `13-2`) inside the export
+    /// directory, if exporting frames is enabled.
+    pub fn new(name: &str) -> Frames {
+        let config = CONFIG.get().filter(|c| c.frames).cloned();
+        let dir = config
+            .as_ref()
+            .map(|c| c.dir.join(name))
+            .unwrap_or_default();
+        Frames::with_config(config, dir)
+    }
+
+    fn with_config(config: Option<Config>, dir: PathBuf) -> Frames {
+        Frames {
+            config,
+            dir,
+            count: 0,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.is_some()
+    }
+
+    /// Write the next frame. `raster` isn't called if exporting is disabled,
+    /// and empty frames are skipped.
+    pub fn push(&mut self, raster: impl FnOnce() -> Raster) -> DynResult<()> {
+        let config = match &self.config {
+            Some(config) => config,
+            None => return Ok(()),
+        };
+
+        let raster = raster();
+        if raster.is_empty() {
+            return Ok(());
+        }
+        if self.count == 0 {
+            std::fs::create_dir_all(&self.dir)?;
+        }
+        let path = (self.dir).join(format!("{:05}.{}", self.count, config.format.extension()));
+        raster.scale(config.scale).save(path)?;
+        self.count += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::convert::TryInto;
+
+    #[test]
+    fn checksums() {
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+    }
+
+    #[test]
+    fn encode() {
+        let img = Image::new(2, vec![true, false, false, true]).unwrap();
+        let raster = Raster::from_image(&img, WHITE, BLACK).scale(2);
+        assert_eq!((raster.width(), raster.height()), (4, 4));
+        assert_eq!(raster.get(1, 1), WHITE);
+        assert_eq!(raster.get(2, 1), BLACK);
+
+        let ppm = raster.to_ppm();
+        assert!(ppm.starts_with(b"P6\n4 4\n255\n"));
+        assert_eq!(ppm.len(), 11 + 4 * 4 * 3);
+
+        let png = raster.to_png();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+        // IHDR: length, type, width, height, and CRC
+        assert_eq!(&png[8..16], b"\0\0\0\x0dIHDR");
+        assert_eq!(&png[16..24], &[0, 0, 0, 4, 0, 0, 0, 4]);
+        assert_eq!(
+            u32::from_be_bytes(png[29..33].try_into().unwrap()),
+            crc32(&png[12..29])
+        );
+        assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+
+        // large images are split over multiple deflate blocks
+        let zlib = zlib_stored(&[7; 70000]);
+        assert_eq!(zlib.len(), 2 + 5 + 0xffff + 5 + (70000 - 0xffff) + 4);
+        assert_eq!(zlib[2], 0);
+        assert_eq!(zlib[2 + 5 + 0xffff], 1);
+    }
+
+    #[test]
+    fn grids() {
+        let mut grid = SparseGrid::new();
+        grid.insert(Point::new(-1, -1), ());
+        grid.insert(Point::new(1, 0), ());
+        let raster = Raster::from_sparse(&grid, |_, c| c.map(|_| WHITE).unwrap_or(BLACK));
+        assert_eq!((raster.width(), raster.height()), (3, 2));
+        assert_eq!(raster.get(0, 0), WHITE);
+        assert_eq!(raster.get(2, 1), WHITE);
+        assert_eq!(raster.get(1, 0), BLACK);
+
+        let empty = Raster::from_sparse(&SparseGrid::<()>::new(), |_, _| BLACK);
+        assert!(empty.is_empty());
+        let path = std::env::temp_dir().join(format!("aoc19-empty-{}.png", std::process::id()));
+        assert!(empty.save(&path).is_err());
+        assert!(!path.exists());
+
+        assert_eq!(ansi_256(196), [255, 0, 0]);
+        assert_eq!(ansi_256(244), [128, 128, 128]);
+    }
+
+    #[test]
+    fn frames() {
+        let mut args = ["--export", "out", "--frames", "--scale", "2"]
+            .map(String::from)
+            .to_vec();
+        let config = parse_config(&mut args).unwrap().unwrap();
+        assert!(args.is_empty());
+        assert_eq!((config.format, config.scale), (Format::Png, 2));
+
+        let mut args = vec!["--frames".to_string()];
+        assert!(parse_config(&mut args).is_err());
+
+        let dir = std::env::temp_dir().join(format!("aoc19-frames-{}", std::process::id()));
+        let config = Config {
+            dir: dir.clone(),
+            format: Format::Ppm,
+            scale: 1,
+            frames: true,
+        };
+        let mut frames = Frames::with_config(Some(config), dir.clone());
+        for i in 0..3 {
+            frames
+                .push(|| Raster::from_fn(1, 1, |_, _| [i; 3]))
+                .unwrap();
+            // empty frames are skipped, rather than numbered
+            frames.push(|| Raster::from_fn(0, 0, |_, _| BLACK)).unwrap();
+        }
+
+        let last = std::fs::read(dir.join("00002.ppm")).unwrap();
+        assert!(!dir.join("00003.ppm").exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(last, b"P6\n1 1\n255\n\x02\x02\x02");
+
+        let mut disabled = Frames::with_config(None, PathBuf::new());
+        disabled.push(|| unreachable!()).unwrap();
+    }
+}
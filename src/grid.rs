@@ -0,0 +1,509 @@
+//! 2D grids, and the points / directions used to navigate them.
+//!
+//! Coordinates follow screen conventions: `x` increases to the right, and `y`
+//! increases _downwards_ (i.e: [`Dir::Up`] is `(0, -1)`).
+
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::iter::FromIterator;
+use std::ops::{Add, AddAssign, Index, IndexMut, Mul, Sub};
+
+use crate::parse::{self, CellResult};
+
+/// A point on a grid.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub const fn new(x: i32, y: i32) -> Point {
+        Point { x, y }
+    }
+
+    /// Manhattan distance from the origin.
+    pub fn manhattan(self) -> i32 {
+        self.x.abs() + self.y.abs()
+    }
+
+    /// The 4 orthogonally adjacent points.
+    pub fn neighbors(self) -> impl Iterator<Item = Point> {
+        Dir::ALL.iter().map(move |&d| self + d)
+    }
+
+    /// The 8 orthogonally and diagonally adjacent points.
+    pub fn neighbors8(self) -> impl Iterator<Item = Point> {
+        (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| Point::new(dx, dy)))
+            .filter(|&d| d != Point::ORIGIN)
+            .map(move |d| self + d)
+    }
+}
+
+impl From<(i32, i32)> for Point {
+    fn from((x, y): (i32, i32)) -> Point {
+        Point { x, y }
+    }
+}
+
+impl From<Point> for (i32, i32) {
+    fn from(p: Point) -> (i32, i32) {
+        (p.x, p.y)
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+    fn add(self, o: Point) -> Point {
+        Point::new(self.x + o.x, self.y + o.y)
+    }
+}
+
+impl Add<Dir> for Point {
+    type Output = Point;
+    fn add(self, d: Dir) -> Point {
+        self + d.delta()
+    }
+}
+
+impl<T> AddAssign<T> for Point
+where
+    Point: Add<T, Output = Point>,
+{
+    fn add_assign(&mut self, o: T) {
+        *self = *self + o;
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+    fn sub(self, o: Point) -> Point {
+        Point::new(self.x - o.x, self.y - o.y)
+    }
+}
+
+impl Mul<i32> for Point {
+    type Output = Point;
+    fn mul(self, n: i32) -> Point {
+        Point::new(self.x * n, self.y * n)
+    }
+}
+
+/// One of the 4 cardinal directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dir {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Dir {
+    /// Every direction, in clockwise order.
+    pub const ALL: [Dir; 4] = [Dir::Up, Dir::Right, Dir::Down, Dir::Left];
+
+    /// The offset of a single step in this direction.
+    pub fn delta(self) -> Point {
+        match self {
+            Dir::Up => Point::new(0, -1),
+            Dir::Right => Point::new(1, 0),
+            Dir::Down => Point::new(0, 1),
+            Dir::Left => Point::new(-1, 0),
+        }
+    }
+
+    /// Rotate 90 degrees clockwise.
+    pub fn turn_right(self) -> Dir {
+        Dir::ALL[(self as usize + 1) % 4]
+    }
+
+    /// Rotate 90 degrees counter-clockwise.
+    pub fn turn_left(self) -> Dir {
+        Dir::ALL[(self as usize + 3) % 4]
+    }
+
+    pub fn reverse(self) -> Dir {
+        Dir::ALL[(self as usize + 2) % 4]
+    }
+}
+
+impl std::str::FromStr for Dir {
+    type Err = String;
+
+    /// Parses `U`/`D`/`L`/`R`, or `N`/`S`/`W`/`E`.
+    fn from_str(s: &str) -> Result<Dir, String> {
+        match s {
+            "U" | "N" => Ok(Dir::Up),
+            "R" | "E" => Ok(Dir::Right),
+            "D" | "S" => Ok(Dir::Down),
+            "L" | "W" => Ok(Dir::Left),
+            _ => Err(format!("invalid direction: {}", s)),
+        }
+    }
+}
+
+/// An inclusive bounding box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    /// The smallest bounding box containing every point, or `None` if there
+    /// aren't any.
+    pub fn of(points: impl IntoIterator<Item = Point>) -> Option<Bounds> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut bounds = Bounds {
+            min: first,
+            max: first,
+        };
+        for p in points {
+            bounds.include(p);
+        }
+        Some(bounds)
+    }
+
+    /// Grow the bounding box to include `p`.
+    pub fn include(&mut self, p: Point) {
+        self.min = Point::new(self.min.x.min(p.x), self.min.y.min(p.y));
+        self.max = Point::new(self.max.x.max(p.x), self.max.y.max(p.y));
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max.x - self.min.x + 1) as usize
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max.y - self.min.y + 1) as usize
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+
+    /// Iterate over every point in the bounding box, in row-major order.
+    pub fn points(self) -> impl Iterator<Item = Point> {
+        (self.min.y..=self.max.y)
+            .flat_map(move |y| (self.min.x..=self.max.x).map(move |x| Point::new(x, y)))
+    }
+
+    /// Draw each point in the bounding box as a string, one line per row.
+    fn render<S: fmt::Display>(self, mut draw: impl FnMut(Point) -> S) -> String {
+        let mut out = String::new();
+        for y in self.min.y..=self.max.y {
+            if y != self.min.y {
+                out.push('\n');
+            }
+            for x in self.min.x..=self.max.x {
+                write!(out, "{}", draw(Point::new(x, y))).unwrap();
+            }
+        }
+        out
+    }
+}
+
+/// Draw a cell as a pair of spaces with the given background color (from the
+/// 256 color ANSI palette).
+fn ansi_color(color: Option<u8>) -> String {
+    match color {
+        Some(color) => format!("\x1b[48;5;{}m  \x1b[0m", color),
+        None => "  ".to_string(),
+    }
+}
+
+/// A dense, fixed-size grid, with it's top-left corner at the origin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    /// Create a new grid with every cell set to `fill`.
+    pub fn new(width: usize, height: usize, fill: T) -> Grid<T> {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Parse a grid out of a block of text, one character per cell. Every
+    /// line must be the same length, and `cell` may reject characters by
+    /// returning an error message.
+    pub fn parse(text: &str, cell: impl FnMut(char) -> CellResult<T>) -> parse::Result<Grid<T>> {
+        parse::grid(text, cell)
+    }
+
+    /// Create a grid from it's cells, in row-major order.
+    pub(crate) fn from_cells(width: usize, height: usize, cells: Vec<T>) -> Grid<T> {
+        assert_eq!(cells.len(), width * height);
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        Bounds {
+            min: Point::ORIGIN,
+            max: Point::new(self.width as i32 - 1, self.height as i32 - 1),
+        }
+    }
+
+    fn idx(&self, p: Point) -> Option<usize> {
+        if p.x < 0 || p.y < 0 || p.x as usize >= self.width || p.y as usize >= self.height {
+            return None;
+        }
+        Some(p.y as usize * self.width + p.x as usize)
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.idx(p).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.idx(p).map(move |i| &mut self.cells[i])
+    }
+
+    /// Iterate over every cell, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.bounds().points().zip(self.cells.iter())
+    }
+
+    /// The in-bounds points orthogonally adjacent to `p`.
+    pub fn neighbors(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        p.neighbors().filter(move |&n| self.idx(n).is_some())
+    }
+
+    /// Render the grid using a character palette.
+    pub fn render(&self, mut palette: impl FnMut(Point, &T) -> char) -> String {
+        self.bounds().render(|p| palette(p, &self[p]))
+    }
+
+    /// Render the grid using a color palette (see [`SparseGrid::render_colors`]).
+    pub fn render_colors(&self, mut palette: impl FnMut(Point, &T) -> Option<u8>) -> String {
+        self.bounds().render(|p| ansi_color(palette(p, &self[p])))
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+    fn index(&self, p: Point) -> &T {
+        self.get(p)
+            .unwrap_or_else(|| panic!("{} is out of bounds", p))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        self.get_mut(p)
+            .unwrap_or_else(|| panic!("{} is out of bounds", p))
+    }
+}
+
+/// An unbounded grid, where most cells are empty.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    cells: HashMap<Point, T>,
+}
+
+impl<T> Default for SparseGrid<T> {
+    fn default() -> SparseGrid<T> {
+        SparseGrid {
+            cells: HashMap::new(),
+        }
+    }
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new() -> SparseGrid<T> {
+        SparseGrid::default()
+    }
+
+    /// Parse a grid out of a block of text, one character per cell, where
+    /// `cell` returns `Ok(None)` for empty cells.
+    pub fn parse(
+        text: &str,
+        cell: impl FnMut(char) -> CellResult<Option<T>>,
+    ) -> parse::Result<SparseGrid<T>> {
+        parse::sparse_grid(text, cell)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.cells.get(&p)
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.cells.get_mut(&p)
+    }
+
+    /// Returns the cell's previous value, if any.
+    pub fn insert(&mut self, p: Point, val: T) -> Option<T> {
+        self.cells.insert(p, val)
+    }
+
+    pub fn remove(&mut self, p: Point) -> Option<T> {
+        self.cells.remove(&p)
+    }
+
+    pub fn entry(&mut self, p: Point) -> std::collections::hash_map::Entry<'_, Point, T> {
+        self.cells.entry(p)
+    }
+
+    /// Iterate over every non-empty cell, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.cells.iter().map(|(&p, v)| (p, v))
+    }
+
+    /// The bounding box of every non-empty cell.
+    pub fn bounds(&self) -> Option<Bounds> {
+        Bounds::of(self.cells.keys().copied())
+    }
+
+    /// Render the grid's bounding box using a character palette, where empty
+    /// cells are passed as `None`.
+    pub fn render(&self, mut palette: impl FnMut(Point, Option<&T>) -> char) -> String {
+        match self.bounds() {
+            Some(bounds) => bounds.render(|p| palette(p, self.get(p))),
+            None => String::new(),
+        }
+    }
+
+    /// Render the grid's bounding box using a color palette, where each cell
+    /// is drawn as two spaces with a background color from the 256 color ANSI
+    /// palette (or left blank, if the palette returns `None`).
+    pub fn render_colors(
+        &self,
+        mut palette: impl FnMut(Point, Option<&T>) -> Option<u8>,
+    ) -> String {
+        match self.bounds() {
+            Some(bounds) => bounds.render(|p| ansi_color(palette(p, self.get(p)))),
+            None => String::new(),
+        }
+    }
+}
+
+impl<T> FromIterator<(Point, T)> for SparseGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Point, T)>>(iter: I) -> SparseGrid<T> {
+        SparseGrid {
+            cells: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> Extend<(Point, T)> for SparseGrid<T> {
+    fn extend<I: IntoIterator<Item = (Point, T)>>(&mut self, iter: I) {
+        self.cells.extend(iter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directions() {
+        let mut p = Point::ORIGIN;
+        let mut dir = Dir::Up;
+        for _ in 0..4 {
+            p += dir;
+            dir = dir.turn_right();
+        }
+        assert_eq!(p, Point::ORIGIN);
+        assert_eq!(Dir::Up.turn_left(), Dir::Left);
+        assert_eq!(Dir::Left.reverse(), Dir::Right);
+        assert_eq!("R".parse::<Dir>().unwrap().delta() * 3, Point::new(3, 0));
+
+        assert_eq!(Point::new(1, 1).neighbors().count(), 4);
+        assert_eq!(Point::new(1, 1).neighbors8().count(), 8);
+        assert_eq!(Point::new(-3, 4).manhattan(), 7);
+    }
+
+    #[test]
+    fn dense() {
+        let grid = Grid::parse("#..\n.#.", |c| match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err("invalid char".to_string()),
+        })
+        .unwrap();
+
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert!(grid[Point::new(1, 1)]);
+        assert_eq!(grid.get(Point::new(3, 0)), None);
+        assert_eq!(grid.neighbors(Point::ORIGIN).count(), 2);
+        assert_eq!(grid.iter().filter(|(_, &c)| c).count(), 2);
+        assert_eq!(grid.render(|_, &c| if c { 'X' } else { ' ' }), "X  \n X ");
+
+        assert!(Grid::parse("#.\n#", |_| Ok(())).is_err());
+        let err = Grid::parse("#?", |c| match c {
+            '#' => Ok(()),
+            _ => Err("bad".to_string()),
+        })
+        .unwrap_err();
+        assert_eq!((err.line, err.col, err.msg.as_str()), (1, 2, "bad"));
+    }
+
+    #[test]
+    fn sparse() {
+        let mut grid = SparseGrid::new();
+        grid.insert(Point::new(-1, 2), 'a');
+        grid.insert(Point::new(1, 3), 'b');
+
+        let bounds = grid.bounds().unwrap();
+        assert_eq!((bounds.width(), bounds.height()), (3, 2));
+        assert!(bounds.contains(Point::new(0, 2)));
+        assert_eq!(bounds.points().count(), 6);
+        assert_eq!(grid.render(|_, c| *c.unwrap_or(&'.')), "a..\n..b");
+        assert_eq!(
+            grid.render_colors(|_, c| c.map(|_| 1)),
+            "\x1b[48;5;1m  \x1b[0m    \n    \x1b[48;5;1m  \x1b[0m"
+        );
+
+        let parsed = SparseGrid::parse(".#\n#.", |c| match c {
+            '#' => Ok(Some(())),
+            '.' => Ok(None),
+            _ => Err("invalid char".to_string()),
+        })
+        .unwrap();
+        assert_eq!(parsed.len(), 2);
+        let err = SparseGrid::parse(".\n.?", |c| match c {
+            '.' => Ok(Some(())),
+            _ => Err("bad".to_string()),
+        })
+        .unwrap_err();
+        assert_eq!((err.line, err.col, err.msg.as_str()), (2, 2, "bad"));
+    }
+}
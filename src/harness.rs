@@ -0,0 +1,434 @@
+//! Utilities for running day solutions.
+
+mod answers;
+pub mod args;
+pub mod bench;
+pub mod cache;
+pub mod capture;
+pub mod examples;
+pub mod input;
+pub mod isolate;
+pub mod json;
+pub mod parallel;
+pub mod scaffold;
+
+use std::time::Duration;
+
+use crate::error::Report;
+use crate::{Answer, DynResult};
+
+use self::answers::{Answers, ANSWERS_PATH};
+use self::args::{Args, Opt};
+use self::input::Input;
+use self::json::{Json, ToJson};
+
+/// A single question's solver.
+pub struct Solution {
+    pub day: &'static str,
+    pub question: &'static str,
+    pub opts: &'static [Opt],
+    /// Why the question is too slow to include when running every question
+    /// (if it is)
+    pub slow: Option<&'static str>,
+    pub run: fn(String, &[String]) -> DynResult<Rendered>,
+}
+
+impl Solution {
+    /// Validate arguments against the question's options.
+    pub fn check_args(&self, args: &[String]) -> DynResult<()> {
+        Args::parse(self.opts, args).map(drop)
+    }
+}
+
+/// A solution's return value, split into it's constituent parts (see
+/// [`Answer`]), along with a JSON representation of the whole thing.
+#[derive(Debug)]
+pub struct Rendered {
+    pub answer: Option<String>,
+    pub details: Option<String>,
+    pub image: Option<String>,
+    pub json: Json,
+}
+
+impl Rendered {
+    pub fn new<T: Answer>(answer: T) -> Rendered {
+        Rendered {
+            answer: answer.answer(),
+            details: answer.details(),
+            image: answer.image(),
+            json: answer.to_json(),
+        }
+    }
+
+    /// A single-line summary of the answer and it's details.
+    pub fn summary(&self) -> String {
+        let answer = self.answer.as_deref().unwrap_or("-");
+        match &self.details {
+            Some(details) => format!("{} ({})", answer, details),
+            None => answer.to_string(),
+        }
+    }
+
+    /// Print the answer, it's details, and it's image.
+    pub fn print(&self) {
+        match (&self.answer, &self.image) {
+            (Some(answer), _) => println!("Answer: {}", answer),
+            (None, Some(_)) => println!("Answer: (see image)"),
+            (None, None) => println!("Answer: -"),
+        }
+        if let Some(details) = &self.details {
+            println!("Details: {}", details);
+        }
+        if let Some(image) = &self.image {
+            println!("{}", image);
+        }
+    }
+}
+
+/// How results are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Human-readable text
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+impl Format {
+    /// Remove a `--format <text|json>` flag from `args`, returning the
+    /// requested format (defaulting to `Text`).
+    pub fn extract(args: &mut Vec<String>) -> DynResult<Format> {
+        let pos = match args.iter().position(|a| a == "--format") {
+            Some(pos) => pos,
+            None => return Ok(Format::Text),
+        };
+
+        let format = match args.get(pos + 1).map(|s| s.as_str()) {
+            Some("text") => Format::Text,
+            Some("json") => Format::Json,
+            Some(other) => return Err(format!("Unknown format: {}", other).into()),
+            None => return Err("--format requires a value (text or json)".into()),
+        };
+
+        args.drain(pos..pos + 2);
+        Ok(format)
+    }
+}
+
+/// Find the solution for a particular day and question.
+pub fn find<'a>(solutions: &'a [Solution], day: &str, question: &str) -> DynResult<&'a Solution> {
+    if !solutions.iter().any(|s| s.day == day) {
+        return Err("Unknown day".into());
+    }
+
+    (solutions.iter())
+        .find(|s| s.day == day && s.question == question)
+        .ok_or_else(|| "Unknown question".into())
+}
+
+/// Print usage information for each of a day's questions.
+pub fn help(solutions: &[Solution], day: &str) -> DynResult<()> {
+    let questions = solutions
+        .iter()
+        .filter(|s| s.day == day)
+        .collect::<Vec<_>>();
+    if questions.is_empty() {
+        return Err("Unknown day".into());
+    }
+
+    println!("Day {}", day);
+    for solution in questions {
+        print!("  {}", args::usage(solution.question, solution.opts));
+    }
+    Ok(())
+}
+
+/// Select the solutions matching an optional `[<day> [<question>]]` filter.
+/// Slow solutions are skipped, unless they're selected explicitly.
+pub fn select<'a>(solutions: &'a [Solution], filter: &[String]) -> DynResult<Vec<&'a Solution>> {
+    let (day, question) = (filter.first(), filter.get(1));
+    let selected = (solutions.iter())
+        .filter(|s| day.map(|d| s.day == d).unwrap_or(true))
+        .filter(|s| question.map(|q| s.question == q).unwrap_or(true))
+        .filter(|s| match s.slow {
+            Some(reason) if question.is_none() => {
+                eprintln!(
+                    "skipping {} {} ({}), select it explicitly to run it",
+                    s.day, s.question, reason
+                );
+                false
+            }
+            _ => true,
+        })
+        .collect::<Vec<_>>();
+
+    if selected.is_empty() {
+        return Err("No matching day / question".into());
+    }
+
+    Ok(selected)
+}
+
+/// Read a day's input from `./inputs/{day}.txt`, stripping any trailing
+/// whitespace.
+pub fn read_input(day: &str) -> DynResult<String> {
+    let input_path = std::path::Path::new(cache::INPUTS_DIR).join(format!("{}.txt", day));
+
+    let mut input = std::fs::read_to_string(&input_path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => format!(
+            "Could not open {}: {} (try `inputs fetch {}`)",
+            input_path.display(),
+            e,
+            day
+        ),
+        _ => format!("Could not open {}: {}", input_path.display(), e),
+    })?;
+    input.truncate(input.trim_end().len());
+    Ok(input)
+}
+
+/// The outcome of running a single solution.
+pub struct Outcome<'a> {
+    pub solution: &'a Solution,
+    /// Name of the input the solution was run against (see [`Input`]).
+    pub input: Option<String>,
+    pub answer: DynResult<Rendered>,
+    pub time: Duration,
+}
+
+impl Outcome<'_> {
+    /// Render the outcome as a JSON object.
+    pub fn to_json(&self) -> Json {
+        let (answer, error) = match &self.answer {
+            Ok(answer) => (answer.json.clone(), Json::Null),
+            Err(e) => (Json::Null, Json::String(Report(&**e).to_string())),
+        };
+
+        let mut fields = vec![
+            ("day", Json::Number(self.solution.day.to_string())),
+            ("question", Json::Number(self.solution.question.to_string())),
+        ];
+        if let Some(input) = &self.input {
+            fields.push(("input", input.to_json()));
+        }
+        fields.extend(vec![
+            ("answer", answer),
+            ("duration_ns", self.time.as_nanos().to_json()),
+            ("error", error),
+        ]);
+
+        Json::object(fields)
+    }
+}
+
+/// Run a solution against it's input, timing how long it takes (excluding
+/// the time spent reading input).
+pub fn run<'a>(solution: &'a Solution, args: &[String]) -> Outcome<'a> {
+    match read_input(solution.day) {
+        Ok(text) => run_input(solution, &Input { name: None, text }, args),
+        Err(e) => Outcome {
+            solution,
+            input: None,
+            answer: Err(e),
+            time: Duration::default(),
+        },
+    }
+}
+
+/// Run a solution against a particular input, timing how long it takes. Any
+/// panics (or timeouts) are reported as errors (see [`isolate`]).
+pub fn run_input<'a>(solution: &'a Solution, input: &Input, args: &[String]) -> Outcome<'a> {
+    let (answer, time) = match solution.check_args(args) {
+        Ok(()) => isolate::run(solution, &input.text, args),
+        Err(e) => (Err(e), Duration::default()),
+    };
+    Outcome {
+        solution,
+        input: input.name.clone(),
+        answer,
+        time,
+    }
+}
+
+/// Run a single solution against each of the given inputs, printing answers
+/// as they complete. Returns an error if the solution failed on any input.
+pub fn run_inputs(
+    solution: &Solution,
+    inputs: &[Input],
+    args: &[String],
+    format: Format,
+) -> DynResult<()> {
+    let mut failed = 0;
+    for input in inputs {
+        let outcome = run_input(solution, input, args);
+        match format {
+            Format::Json => println!("{}", outcome.to_json()),
+            Format::Text => {
+                if let Some(name) = &outcome.input {
+                    println!("==> {} <==", name);
+                }
+                match &outcome.answer {
+                    Ok(answer) => answer.print(),
+                    // a lone input's error is returned instead
+                    Err(e) if inputs.len() > 1 => println!("Error: {:#}", Report(&**e)),
+                    Err(_) => {}
+                }
+            }
+        }
+
+        if let Err(e) = outcome.answer {
+            // keep the original error when there's only one input
+            if inputs.len() == 1 {
+                return Err(e);
+            }
+            failed += 1;
+        }
+    }
+
+    if failed != 0 {
+        return Err(format!("{} of {} inputs failed", failed, inputs.len()).into());
+    }
+
+    Ok(())
+}
+
+/// Run every solution with default arguments, printing a summary table of
+/// answers and timings (or a JSON line per solution) as they complete.
+/// Returns an error if any solution failed.
+pub fn run_all(solutions: &[&Solution], format: Format) -> DynResult<()> {
+    if format == Format::Text {
+        print_header();
+    }
+
+    let mut failed = 0;
+    let mut total = Duration::default();
+    for solution in solutions {
+        let outcome = run(solution, &[]);
+        match format {
+            Format::Text => print_row(&outcome),
+            Format::Json => println!("{}", outcome.to_json()),
+        }
+
+        total += outcome.time;
+        if outcome.answer.is_err() {
+            failed += 1;
+        }
+    }
+
+    if format == Format::Text {
+        println!("{:>3} {:>2} {:>12.3?}", "", "", total);
+    }
+
+    if failed != 0 {
+        return Err(format!("{} of {} parts failed", failed, solutions.len()).into());
+    }
+
+    Ok(())
+}
+
+fn print_header() {
+    println!("{:>3} {:>2} {:>12}  Answer", "Day", "Q", "Time");
+}
+
+fn print_row(outcome: &Outcome) {
+    let answer = match &outcome.answer {
+        Ok(answer) => answer.summary(),
+        Err(e) => format!("ERROR: {}", Report(&**e)),
+    };
+    println!(
+        "{:>3} {:>2} {:>12.3?}  {}",
+        outcome.solution.day, outcome.solution.question, outcome.time, answer
+    );
+
+    // only show images which couldn't be read
+    let unread = outcome.answer.as_ref().ok().filter(|a| a.answer.is_none());
+    if let Some(image) = unread.and_then(|a| a.image.as_ref()) {
+        for ln in image.lines() {
+            println!("{:>20}{}", "", ln);
+        }
+    }
+}
+
+/// Run every solution, comparing it's answer against the known answers file.
+/// Returns an error if any answer changed, or any solution failed.
+pub fn verify(solutions: &[&Solution]) -> DynResult<()> {
+    let answers = Answers::load(ANSWERS_PATH)?;
+
+    let mut failed = 0;
+    for solution in solutions {
+        let outcome = run(solution, &[]);
+        let (day, question) = (solution.day, solution.question);
+
+        let expected = answers.get(day, question);
+        let (passed, status) = match (&outcome.answer, expected) {
+            (Err(e), _) => (false, format!("ERROR: {}", Report(&**e))),
+            (Ok(Rendered { answer: None, .. }), _) => (true, "no answer".to_string()),
+            (Ok(_), None) => (true, "unrecorded".to_string()),
+            (
+                Ok(Rendered {
+                    answer: Some(actual),
+                    ..
+                }),
+                Some(expected),
+            ) if actual == expected => (true, "ok".to_string()),
+            (
+                Ok(Rendered {
+                    answer: Some(actual),
+                    ..
+                }),
+                Some(expected),
+            ) => (false, format!("MISMATCH\n  - {}\n  + {}", expected, actual)),
+        };
+
+        if !passed {
+            failed += 1;
+        }
+
+        println!(
+            "{:>3} {:>2} {:>12.3?}  {}",
+            day, question, outcome.time, status
+        );
+    }
+
+    if failed != 0 {
+        return Err(format!(
+            "{} of {} parts failed verification",
+            failed,
+            solutions.len()
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Run every solution, recording it's answer in the known answers file.
+/// Solutions which fail are left untouched.
+pub fn record(solutions: &[&Solution]) -> DynResult<()> {
+    let mut answers = Answers::load(ANSWERS_PATH)?;
+
+    for solution in solutions {
+        let outcome = run(solution, &[]);
+        let (day, question) = (solution.day, solution.question);
+
+        let status = match outcome.answer {
+            Err(e) => format!("ERROR: {}", Report(&*e)),
+            Ok(Rendered { answer: None, .. }) => "no answer".to_string(),
+            Ok(Rendered {
+                answer: Some(answer),
+                ..
+            }) => match answers.set(day, question, answer.clone())? {
+                None => format!("recorded {}", answer),
+                Some(old) if old == answer => "unchanged".to_string(),
+                Some(old) => format!("updated\n  - {}\n  + {}", old, answer),
+            },
+        };
+
+        println!(
+            "{:>3} {:>2} {:>12.3?}  {}",
+            day, question, outcome.time, status
+        );
+    }
+
+    answers.save()
+}
@@ -0,0 +1,81 @@
+//! A registry of known answers, used to catch regressions.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::DynResult;
+
+/// Default location of the known answers file.
+pub const ANSWERS_PATH: &str = "./answers.txt";
+
+/// Known answers, keyed by (day, question), stored as their canonical
+/// (submittable) form.
+///
+/// On disk, each line is of the form `<day> <question> <answer>`. Blank lines
+/// and lines starting with `#` are ignored.
+pub struct Answers {
+    path: PathBuf,
+    answers: BTreeMap<(usize, usize), String>,
+}
+
+fn key(day: &str, question: &str) -> DynResult<(usize, usize)> {
+    Ok((day.parse()?, question.parse()?))
+}
+
+impl Answers {
+    /// Load known answers from `path`. A missing file is treated as empty.
+    pub fn load(path: impl AsRef<Path>) -> DynResult<Answers> {
+        let path = path.as_ref();
+        let mut answers = BTreeMap::new();
+
+        let file = match std::fs::read_to_string(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("Could not open {}: {}", path.display(), e).into()),
+        };
+
+        for (i, ln) in file.lines().enumerate() {
+            if ln.trim().is_empty() || ln.starts_with('#') {
+                continue;
+            }
+
+            let mut ln = ln.splitn(3, ' ');
+            let parsed = match (ln.next(), ln.next(), ln.next()) {
+                (Some(day), Some(question), Some(answer)) => {
+                    key(day, question).map(|k| (k, answer.to_string()))
+                }
+                _ => Err("expected `<day> <question> <answer>`".into()),
+            };
+            let (k, answer) = parsed.map_err(|e| format!("{}:{}: {}", path.display(), i + 1, e))?;
+            answers.insert(k, answer);
+        }
+
+        Ok(Answers {
+            path: path.to_path_buf(),
+            answers,
+        })
+    }
+
+    /// Write the known answers back to disk.
+    pub fn save(&self) -> DynResult<()> {
+        let mut file = String::from("# <day> <question> <answer>\n");
+        for ((day, question), answer) in &self.answers {
+            file += &format!("{} {} {}\n", day, question, answer);
+        }
+
+        std::fs::write(&self.path, file)
+            .map_err(|e| format!("Could not write {}: {}", self.path.display(), e).into())
+    }
+
+    /// Returns the known answer for a day and question (if there is one).
+    pub fn get(&self, day: &str, question: &str) -> Option<&str> {
+        let k = key(day, question).ok()?;
+        self.answers.get(&k).map(|s| s.as_str())
+    }
+
+    /// Record a new known answer for a day and question, returning the
+    /// previous answer (if there was one).
+    pub fn set(&mut self, day: &str, question: &str, answer: String) -> DynResult<Option<String>> {
+        Ok(self.answers.insert(key(day, question)?, answer))
+    }
+}
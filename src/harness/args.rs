@@ -0,0 +1,198 @@
+//! Declarative per-question argument parsing.
+//!
+//! Each question which accepts arguments describes them with a list of
+//! [`Opt`]s. Arguments are either flags (a word which enables some variant of
+//! the solution, e.g: `vis`), or values, which are assigned positionally (or
+//! explicitly, using `name=value`).
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::DynResult;
+
+/// The type of an option.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    /// Enabled when it's name is passed as an argument.
+    Flag,
+    /// A non-negative integer.
+    Uint,
+}
+
+/// Description of a single option.
+#[derive(Debug)]
+pub struct Opt {
+    pub name: &'static str,
+    pub kind: Kind,
+    pub default: Option<&'static str>,
+    pub help: &'static str,
+}
+
+impl Opt {
+    pub const fn flag(name: &'static str, help: &'static str) -> Opt {
+        Opt {
+            name,
+            kind: Kind::Flag,
+            default: None,
+            help,
+        }
+    }
+
+    pub const fn uint(name: &'static str, default: &'static str, help: &'static str) -> Opt {
+        Opt {
+            name,
+            kind: Kind::Uint,
+            default: Some(default),
+            help,
+        }
+    }
+
+    fn validate(&self, val: &str) -> DynResult<()> {
+        match self.kind {
+            Kind::Flag => Ok(()),
+            Kind::Uint => match val.parse::<u64>() {
+                Ok(_) => Ok(()),
+                Err(_) => Err(format!("`{}` expects a number, got `{}`", self.name, val).into()),
+            },
+        }
+    }
+}
+
+/// Arguments which have been validated against a list of options.
+#[derive(Debug)]
+pub struct Args {
+    flags: Vec<&'static str>,
+    values: HashMap<&'static str, String>,
+}
+
+impl Args {
+    /// Parse and validate `args` against `opts`.
+    pub fn parse(opts: &'static [Opt], args: &[String]) -> DynResult<Args> {
+        let mut flags = Vec::new();
+        let mut values = HashMap::new();
+        let mut positional = opts.iter().filter(|o| o.kind != Kind::Flag);
+
+        for arg in args {
+            let flag = opts
+                .iter()
+                .find(|o| o.kind == Kind::Flag && o.name == arg.as_str());
+            if let Some(flag) = flag {
+                if flags.contains(&flag.name) {
+                    return Err(format!("duplicate option `{}`", flag.name).into());
+                }
+                flags.push(flag.name);
+                continue;
+            }
+
+            let (opt, val) = match arg.find('=') {
+                Some(idx) => {
+                    let (name, val) = (&arg[..idx], &arg[idx + 1..]);
+                    let opt = opts
+                        .iter()
+                        .find(|o| o.kind != Kind::Flag && o.name == name)
+                        .ok_or_else(|| format!("unknown option `{}` (see --help)", name))?;
+                    (opt, val)
+                }
+                None => {
+                    let opt = positional
+                        .find(|o| !values.contains_key(o.name))
+                        .ok_or_else(|| format!("unexpected argument `{}` (see --help)", arg))?;
+                    (opt, arg.as_str())
+                }
+            };
+
+            opt.validate(val)?;
+            if values.insert(opt.name, val.to_string()).is_some() {
+                return Err(format!("duplicate option `{}`", opt.name).into());
+            }
+        }
+
+        for opt in opts {
+            if let Some(default) = opt.default {
+                values
+                    .entry(opt.name)
+                    .or_insert_with(|| default.to_string());
+            }
+        }
+
+        Ok(Args { flags, values })
+    }
+
+    /// Check if a flag was passed.
+    pub fn flag(&self, name: &str) -> bool {
+        self.flags.contains(&name)
+    }
+
+    /// Return the value of an option, parsed as `T`.
+    pub fn get<T: FromStr>(&self, name: &str) -> DynResult<T> {
+        let val = (self.values.get(name)).ok_or_else(|| format!("no such option `{}`", name))?;
+        val.parse()
+            .map_err(|_| format!("invalid value for `{}`: {}", name, val).into())
+    }
+}
+
+/// Render usage information for a question's options.
+pub fn usage(question: &str, opts: &[Opt]) -> String {
+    if opts.is_empty() {
+        return format!("q{} (no options)\n", question);
+    }
+
+    let mut s = format!("q{}", question);
+    for opt in opts {
+        match opt.kind {
+            Kind::Flag => s += &format!(" [{}]", opt.name),
+            Kind::Uint => s += &format!(" [<{}>]", opt.name),
+        }
+    }
+    s += "\n";
+
+    for opt in opts {
+        s += &format!("      {:<12} {}", opt.name, opt.help);
+        if let Some(default) = opt.default {
+            s += &format!(" (default: {})", default);
+        }
+        s += "\n";
+    }
+
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OPTS: &[Opt] = &[
+        Opt::flag("vis", "visualize"),
+        Opt::uint("iters", "10", "iterations"),
+        Opt::uint("delay", "0", "delay"),
+    ];
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse() {
+        let parsed = Args::parse(OPTS, &[]).unwrap();
+        assert!(!parsed.flag("vis"));
+        assert_eq!(parsed.get::<usize>("iters").unwrap(), 10);
+
+        let parsed = Args::parse(OPTS, &args(&["5", "vis", "delay=3"])).unwrap();
+        assert!(parsed.flag("vis"));
+        assert_eq!(parsed.get::<usize>("iters").unwrap(), 5);
+        assert_eq!(parsed.get::<u64>("delay").unwrap(), 3);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Args::parse(OPTS, &args(&["abc"])).is_err());
+        assert!(Args::parse(OPTS, &args(&["1", "2", "3"])).is_err());
+        assert!(Args::parse(OPTS, &args(&["speed=3"])).is_err());
+        assert!(Args::parse(&[], &args(&["vis"])).is_err());
+
+        let err = Args::parse(OPTS, &args(&["5", "iters=6"])).unwrap_err();
+        assert_eq!(err.to_string(), "duplicate option `iters`");
+        assert!(Args::parse(OPTS, &args(&["delay=1", "delay=2"])).is_err());
+        assert!(Args::parse(OPTS, &args(&["vis", "vis"])).is_err());
+    }
+}
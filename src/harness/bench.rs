@@ -0,0 +1,204 @@
+//! Repeatedly run solutions to measure how long they take.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use super::{isolate, read_input, Solution};
+use crate::error::Report;
+use crate::DynResult;
+
+/// Benchmark configuration.
+pub struct Options {
+    /// Number of timed iterations
+    pub iters: usize,
+    /// Number of untimed iterations to run beforehand
+    pub warmup: usize,
+    /// Baseline file to compare results against
+    pub baseline: Option<String>,
+    /// Baseline file to save results to
+    pub save: Option<String>,
+    /// Arguments passed through to each solution
+    pub args: Vec<String>,
+}
+
+impl Options {
+    /// Parse benchmark options out of `args`, returning any remaining
+    /// positional arguments. Anything after a `--` is passed through to the
+    /// solutions.
+    pub fn parse(args: &[String]) -> DynResult<(Options, Vec<String>)> {
+        let mut opts = Options {
+            iters: 10,
+            warmup: 1,
+            baseline: None,
+            save: None,
+            args: Vec::new(),
+        };
+        let mut positional = Vec::new();
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut val = || args.next().ok_or(format!("{} requires a value", arg));
+            match arg.as_str() {
+                "-n" => opts.iters = val()?.parse().map_err(|_| "invalid iteration count")?,
+                "--warmup" => opts.warmup = val()?.parse().map_err(|_| "invalid warmup count")?,
+                "--baseline" => opts.baseline = Some(val()?.clone()),
+                "--save" => opts.save = Some(val()?.clone()),
+                "--" => {
+                    opts.args = args.cloned().collect();
+                    break;
+                }
+                _ => positional.push(arg.clone()),
+            }
+        }
+
+        if opts.iters == 0 {
+            return Err("must run at least 1 iteration".into());
+        }
+
+        Ok((opts, positional))
+    }
+}
+
+/// Summary statistics of a set of timings.
+struct Summary {
+    min: Duration,
+    median: Duration,
+    mean: Duration,
+    stddev: Duration,
+}
+
+impl Summary {
+    fn new(mut times: Vec<Duration>) -> Summary {
+        times.sort();
+
+        let n = times.len() as f64;
+        let secs = times.iter().map(|t| t.as_secs_f64());
+        let mean = secs.clone().sum::<f64>() / n;
+        let var = secs.map(|t| (t - mean).powi(2)).sum::<f64>() / n;
+
+        let mid = times.len() / 2;
+        let median = if times.len().is_multiple_of(2) {
+            (times[mid - 1] + times[mid]) / 2
+        } else {
+            times[mid]
+        };
+
+        Summary {
+            min: times[0],
+            median,
+            mean: Duration::from_secs_f64(mean),
+            stddev: Duration::from_secs_f64(var.sqrt()),
+        }
+    }
+}
+
+/// Median timings, keyed by (day, question), stored in nanoseconds.
+///
+/// On disk, each line is of the form `<day> <question> <median ns>`.
+fn load_baseline(path: &str) -> DynResult<BTreeMap<(String, String), u128>> {
+    let file = match std::fs::read_to_string(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(format!("Could not open {}: {}", path, e).into()),
+    };
+
+    let mut baseline = BTreeMap::new();
+    for (i, ln) in file.lines().enumerate() {
+        let mut ln = ln.split_whitespace();
+        match (ln.next(), ln.next(), ln.next().map(|t| t.parse())) {
+            (Some(day), Some(question), Some(Ok(ns))) => {
+                baseline.insert((day.to_string(), question.to_string()), ns);
+            }
+            _ => return Err(format!("{}:{}: malformed baseline", path, i + 1).into()),
+        }
+    }
+    Ok(baseline)
+}
+
+fn save_baseline(path: &str, baseline: &BTreeMap<(String, String), u128>) -> DynResult<()> {
+    let mut keys = baseline.keys().collect::<Vec<_>>();
+    // sort numerically, instead of lexicographically
+    keys.sort_by_key(|(d, q)| (d.parse::<usize>().ok(), q.parse::<usize>().ok()));
+
+    let file = keys
+        .into_iter()
+        .map(|k| format!("{} {} {}\n", k.0, k.1, baseline[k]))
+        .collect::<String>();
+
+    std::fs::write(path, file).map_err(|e| format!("Could not write {}: {}", path, e).into())
+}
+
+/// Time every iteration of a solution (including the warmup iterations).
+/// Input I/O is excluded from the timings.
+fn time_solution(solution: &Solution, opts: &Options) -> DynResult<Vec<Duration>> {
+    solution.check_args(&opts.args)?;
+    let input = read_input(solution.day)?;
+
+    (0..opts.warmup + opts.iters)
+        .map(|_| match isolate::run(solution, &input, &opts.args) {
+            (Ok(_), time) => Ok(time),
+            (Err(e), _) => Err(e),
+        })
+        .collect()
+}
+
+/// Benchmark each solution, printing timing statistics (and the change
+/// relative to the baseline, if one was provided). Solutions are isolated
+/// like any other run (see [`isolate`]), and any which fail are reported
+/// without stopping the rest of the benchmark.
+pub fn bench(solutions: &[&Solution], opts: &Options) -> DynResult<()> {
+    let baseline = match &opts.baseline {
+        Some(path) => Some(load_baseline(path)?),
+        None => None,
+    };
+    let mut results = match &opts.save {
+        Some(path) => load_baseline(path)?,
+        None => BTreeMap::new(),
+    };
+
+    println!(
+        "{:>3} {:>2} {:>12} {:>12} {:>12} {:>12}  vs. Baseline",
+        "Day", "Q", "Min", "Median", "Mean", "Std. Dev"
+    );
+
+    let mut failed = 0;
+    for solution in solutions {
+        let (day, question) = (solution.day, solution.question);
+        let times = match time_solution(solution, opts) {
+            Ok(mut times) => times.split_off(opts.warmup),
+            Err(e) => {
+                println!("{:>3} {:>2}  ERROR: {}", day, question, Report(&*e));
+                failed += 1;
+                continue;
+            }
+        };
+
+        let summary = Summary::new(times);
+        let key = (day.to_string(), question.to_string());
+
+        let vs_baseline = match baseline.as_ref().and_then(|b| b.get(&key)) {
+            Some(&old) => {
+                let change = (summary.median.as_nanos() as f64 / old as f64 - 1.) * 100.;
+                format!("{:+.1}%", change)
+            }
+            None => "-".to_string(),
+        };
+
+        println!(
+            "{:>3} {:>2} {:>12.3?} {:>12.3?} {:>12.3?} {:>12.3?}  {}",
+            day, question, summary.min, summary.median, summary.mean, summary.stddev, vs_baseline
+        );
+
+        results.insert(key, summary.median.as_nanos());
+    }
+
+    if let Some(path) = &opts.save {
+        save_baseline(path, &results)?;
+    }
+
+    if failed != 0 {
+        return Err(format!("{} of {} parts failed", failed, solutions.len()).into());
+    }
+
+    Ok(())
+}
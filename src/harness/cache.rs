@@ -0,0 +1,672 @@
+//! Management of the `inputs/` directory.
+//!
+//! A manifest of per-day checksums is kept alongside the inputs so that
+//! accidental edits can be caught. Checksums ignore line endings and trailing
+//! whitespace (the same way `read_input` strips it), so an input can be
+//! normalized without needing to re-record it's checksum. New inputs are
+//! stored normalized (i.e: with `\n` line endings and a single trailing
+//! newline).
+
+use std::collections::BTreeMap;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use crate::DynResult;
+
+/// Default location of the inputs directory.
+pub const INPUTS_DIR: &str = "./inputs";
+
+/// Name of the checksum manifest within the inputs directory.
+const MANIFEST: &str = "checksums.txt";
+
+/// Default source of puzzle inputs.
+const AOC_URL: &str = "https://adventofcode.com/2019";
+
+/// How long [`HttpProvider`] waits to connect to a server, or to hear back
+/// from it, before giving up.
+const HTTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Normalize an input's line endings and trailing whitespace.
+pub fn normalize(text: &str) -> String {
+    format!("{}\n", text.replace("\r\n", "\n").trim_end())
+}
+
+/// Whether an input is well-formed, i.e: it's already normalized, or only
+/// lacks the trailing newline (as older, hand-saved, inputs do).
+fn is_normalized(text: &str) -> bool {
+    let normalized = normalize(text);
+    text == normalized || text == normalized.trim_end()
+}
+
+/// 64-bit FNV-1a hash of an input's contents, ignoring line endings and
+/// trailing whitespace.
+/// This is only meant to catch accidental changes.
+pub fn checksum(text: &str) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    for b in normalize(text).bytes() {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+/// The state of a single day's input file.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Status {
+    /// Doesn't exist, or is an empty placeholder
+    Missing,
+    /// Present, but has no recorded checksum
+    Unrecorded,
+    /// Present, but doesn't match it's recorded checksum
+    Modified,
+    /// Matches it's recorded checksum, but has stray whitespace (`\r\n` line
+    /// endings, or extra trailing blank lines)
+    Unnormalized,
+    Ok,
+}
+
+/// The inputs directory, and it's checksum manifest.
+pub struct Cache {
+    dir: PathBuf,
+    manifest: BTreeMap<u32, u64>,
+}
+
+impl Cache {
+    /// Open the inputs directory at `dir`, loading it's manifest (if any).
+    pub fn open(dir: impl AsRef<Path>) -> DynResult<Cache> {
+        let dir = dir.as_ref().to_path_buf();
+        let path = dir.join(MANIFEST);
+
+        let file = match std::fs::read_to_string(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("Could not open {}: {}", path.display(), e).into()),
+        };
+
+        let mut manifest = BTreeMap::new();
+        for (i, ln) in file.lines().enumerate() {
+            if ln.trim().is_empty() || ln.starts_with('#') {
+                continue;
+            }
+
+            let mut ln = ln.split_whitespace();
+            let parsed = match (ln.next(), ln.next()) {
+                (Some(day), Some(sum)) => day
+                    .parse()
+                    .ok()
+                    .zip(u64::from_str_radix(sum, 16).ok())
+                    .ok_or("invalid day or checksum"),
+                _ => Err("expected `<day> <checksum>`"),
+            };
+            let (day, sum) = parsed.map_err(|e| format!("{}:{}: {}", path.display(), i + 1, e))?;
+            manifest.insert(day, sum);
+        }
+
+        Ok(Cache { dir, manifest })
+    }
+
+    /// Write the manifest back to disk.
+    pub fn save(&self) -> DynResult<()> {
+        let mut file = String::from("# <day> <checksum (FNV-1a of the normalized input)>\n");
+        for (day, sum) in &self.manifest {
+            file += &format!("{} {:016x}\n", day, sum);
+        }
+
+        let path = self.dir.join(MANIFEST);
+        std::fs::write(&path, file)
+            .map_err(|e| format!("Could not write {}: {}", path.display(), e).into())
+    }
+
+    fn path(&self, day: u32) -> PathBuf {
+        self.dir.join(format!("{}.txt", day))
+    }
+
+    fn read(&self, day: u32) -> DynResult<Option<String>> {
+        let path = self.path(day);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Ok(Some(text)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Could not open {}: {}", path.display(), e).into()),
+        }
+    }
+
+    /// Check the state of a day's input.
+    pub fn status(&self, day: u32) -> DynResult<Status> {
+        let text = match self.read(day)? {
+            Some(text) if !text.trim().is_empty() => text,
+            _ => return Ok(Status::Missing),
+        };
+
+        Ok(match self.manifest.get(&day) {
+            None => Status::Unrecorded,
+            Some(&sum) if sum != checksum(&text) => Status::Modified,
+            Some(_) if !is_normalized(&text) => Status::Unnormalized,
+            Some(_) => Status::Ok,
+        })
+    }
+
+    /// Store a day's input (normalized), and record it's checksum. Refuses to
+    /// replace an existing input with different contents unless `force` is
+    /// set. Returns false if the input was already up to date (existing
+    /// inputs which only differ in trailing whitespace are left as-is).
+    pub fn store(&mut self, day: u32, text: &str, force: bool) -> DynResult<bool> {
+        let text = normalize(text);
+        if let Some(existing) = self.read(day)? {
+            let unchanged = normalize(&existing) == text;
+            if unchanged && self.manifest.get(&day) == Some(&checksum(&text)) {
+                return Ok(false);
+            }
+            if unchanged {
+                self.manifest.insert(day, checksum(&text));
+                return Ok(true);
+            }
+            if !force && !existing.trim().is_empty() {
+                return Err(format!(
+                    "{} already exists with different contents (use --force to replace it)",
+                    self.path(day).display()
+                )
+                .into());
+            }
+        }
+
+        std::fs::create_dir_all(&self.dir)?;
+        std::fs::write(self.path(day), &text)?;
+        self.manifest.insert(day, checksum(&text));
+        Ok(true)
+    }
+
+    /// Normalize an existing input's whitespace in-place. Only inputs which
+    /// still match their recorded checksum are touched, anything else is left
+    /// as-is. Returns the input's status beforehand.
+    pub fn normalize(&mut self, day: u32) -> DynResult<Status> {
+        let status = self.status(day)?;
+        if status == Status::Unnormalized {
+            if let Some(text) = self.read(day)? {
+                std::fs::write(self.path(day), normalize(&text))?;
+            }
+        }
+        Ok(status)
+    }
+}
+
+/// A source of puzzle inputs.
+pub trait Provider {
+    /// Fetch the input for a particular day.
+    fn fetch(&self, day: u32) -> DynResult<String>;
+}
+
+/// Fetches inputs from a plain HTTP server (e.g: a local mirror), using a
+/// `<base>/day/<day>/input` URL scheme.
+pub struct HttpProvider {
+    host: String,
+    base_path: String,
+    session: Option<String>,
+}
+
+impl HttpProvider {
+    /// Create a new provider for an `http://host[:port][/path]` URL.
+    pub fn new(url: &str, session: Option<String>) -> DynResult<HttpProvider> {
+        let url = url
+            .strip_prefix("http://")
+            .ok_or("HttpProvider only supports http:// URLs")?;
+        let (host, path) = match url.find('/') {
+            Some(idx) => url.split_at(idx),
+            None => (url, ""),
+        };
+
+        Ok(HttpProvider {
+            host: host.to_string(),
+            base_path: path.trim_end_matches('/').to_string(),
+            session,
+        })
+    }
+}
+
+impl Provider for HttpProvider {
+    fn fetch(&self, day: u32) -> DynResult<String> {
+        let addr = match self.host.contains(':') {
+            true => self.host.clone(),
+            false => format!("{}:80", self.host),
+        };
+        let mut conn = connect(&addr)?;
+
+        // HTTP/1.0 keeps things simple: no chunked encoding, no keep-alive
+        let mut req = format!(
+            "GET {}/day/{}/input HTTP/1.0\r\nHost: {}\r\n",
+            self.base_path, day, self.host
+        );
+        if let Some(session) = &self.session {
+            req += &format!("Cookie: {}\r\n", session);
+        }
+        req += "\r\n";
+        conn.write_all(req.as_bytes())?;
+
+        let mut resp = Vec::new();
+        conn.read_to_end(&mut resp)?;
+        let resp = String::from_utf8(resp)?;
+
+        let (head, body) = resp
+            .split_once("\r\n\r\n")
+            .ok_or("malformed HTTP response")?;
+        let status = head.lines().next().unwrap_or("");
+        match status.split_whitespace().nth(1) {
+            Some("200") => Ok(body.to_string()),
+            _ => Err(format!("could not fetch day {}: {}", day, status).into()),
+        }
+    }
+}
+
+/// Connect to `addr`, trying each address it resolves to in turn.
+fn connect(addr: &str) -> DynResult<TcpStream> {
+    let mut err = format!("could not resolve {}", addr).into();
+    for addr in addr.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, HTTP_TIMEOUT) {
+            Ok(conn) => {
+                conn.set_read_timeout(Some(HTTP_TIMEOUT))?;
+                conn.set_write_timeout(Some(HTTP_TIMEOUT))?;
+                return Ok(conn);
+            }
+            Err(e) => err = e.into(),
+        }
+    }
+    Err(err)
+}
+
+/// Fetches inputs from Advent of Code (or anything else requiring HTTPS) by
+/// shelling out to `curl`. [`HttpProvider`] can't be used for these, since
+/// speaking TLS would mean pulling in a TLS implementation as a dependency.
+pub struct CurlProvider {
+    base_url: String,
+    session: Option<String>,
+}
+
+impl CurlProvider {
+    pub fn new(base_url: &str, session: Option<String>) -> CurlProvider {
+        CurlProvider {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session,
+        }
+    }
+}
+
+/// A curl config file (as read by `curl -K`) which sends `session` as the
+/// request's cookie.
+fn curl_config(session: &str) -> String {
+    let header = format!("cookie: {}", session)
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"");
+    format!("header = \"{}\"\n", header)
+}
+
+impl Provider for CurlProvider {
+    fn fetch(&self, day: u32) -> DynResult<String> {
+        let mut cmd = Command::new("curl");
+        cmd.args(["--silent", "--show-error", "--fail", "--compressed"]);
+        cmd.arg(format!("{}/day/{}/input", self.base_url, day));
+        // the cookie is passed over stdin, so it doesn't show up in `ps`
+        cmd.args(["-K", "-"]);
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| format!("Could not run curl: {}", e))?;
+        let config = match &self.session {
+            Some(session) => curl_config(session),
+            None => String::new(),
+        };
+        // dropping stdin closes it, letting curl know the config is complete
+        child.stdin.take().unwrap().write_all(config.as_bytes())?;
+        let output = child.wait_with_output()?;
+        if !output.status.success() {
+            let err = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("could not fetch day {}: {}", day, err.trim()).into());
+        }
+        Ok(String::from_utf8(output.stdout)?)
+    }
+}
+
+/// Construct a provider for `url`, authenticating with the contents of
+/// `cookie.txt` (if present).
+pub fn provider(url: &str) -> DynResult<Box<dyn Provider>> {
+    let session = match std::fs::read_to_string("./cookie.txt") {
+        Ok(cookie) => Some(cookie.trim().to_string()),
+        Err(_) => None,
+    };
+
+    if url.starts_with("http://") {
+        Ok(Box::new(HttpProvider::new(url, session)?))
+    } else {
+        Ok(Box::new(CurlProvider::new(url, session)))
+    }
+}
+
+/// Parse a day number out of an input's file name (e.g: `3.txt`, `day3.txt`,
+/// `03`), ignoring any leading directories.
+fn day_from_name(name: &str) -> Option<u32> {
+    let name = name.rsplit('/').next()?;
+    let name = name.strip_suffix(".txt").unwrap_or(name);
+    let name = name.strip_prefix("day").unwrap_or(name);
+    name.parse().ok().filter(|d| (1..=25).contains(d))
+}
+
+/// Read every regular file out of an (uncompressed) tar archive.
+fn read_tar(data: &[u8]) -> DynResult<Vec<(String, Vec<u8>)>> {
+    let field = |header: &[u8], range: std::ops::Range<usize>| {
+        let f = &header[range];
+        let end = f.iter().position(|&b| b == 0).unwrap_or(f.len());
+        String::from_utf8_lossy(&f[..end]).trim().to_string()
+    };
+
+    let mut files = Vec::new();
+    let mut pos = 0;
+    while pos + 512 <= data.len() {
+        let header = &data[pos..pos + 512];
+        // the archive ends with (at least) one empty block
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+
+        let name = match field(header, 345..500) {
+            prefix if !prefix.is_empty() => format!("{}/{}", prefix, field(header, 0..100)),
+            _ => field(header, 0..100),
+        };
+        let size = usize::from_str_radix(&field(header, 124..136), 8)
+            .map_err(|_| format!("invalid tar header for {}", name))?;
+        let kind = header[156];
+
+        // sizes come straight from the archive, so don't trust them
+        let start = pos + 512;
+        let end = start
+            .checked_add(size)
+            .filter(|&end| end <= data.len())
+            .ok_or_else(|| format!("truncated tar archive (in {})", name))?;
+        if kind == b'0' || kind == 0 {
+            files.push((name, data[start..end].to_vec()));
+        }
+
+        pos = end.div_ceil(512) * 512;
+    }
+
+    Ok(files)
+}
+
+/// Import inputs from a directory or an (uncompressed) tar archive, returning
+/// the (day, contents) of each input found.
+fn import(path: &Path) -> DynResult<Vec<(u32, String)>> {
+    let files = if path.is_dir() {
+        let mut files = Vec::new();
+        for entry in std::fs::read_dir(path)? {
+            let path = entry?.path();
+            if path.is_file() {
+                let name = path.file_name().unwrap().to_string_lossy().into_owned();
+                files.push((name, std::fs::read(&path)?));
+            }
+        }
+        files
+    } else {
+        let data =
+            std::fs::read(path).map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+        read_tar(&data)?
+    };
+
+    let mut inputs = Vec::new();
+    for (name, contents) in files {
+        if let Some(day) = day_from_name(&name) {
+            let contents =
+                String::from_utf8(contents).map_err(|_| format!("{} is not valid UTF-8", name))?;
+            inputs.push((day, contents));
+        }
+    }
+    inputs.sort();
+    Ok(inputs)
+}
+
+/// Entry point for the `inputs` subcommand. `days` lists every registered day.
+pub fn run(args: &[String], days: &[u32]) -> DynResult<()> {
+    let mut force = false;
+    let mut url = AOC_URL.to_string();
+    let mut positional = Vec::new();
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--force" => force = true,
+            "--provider" => url = iter.next().ok_or("--provider requires a URL")?.clone(),
+            _ => positional.push(arg.as_str()),
+        }
+    }
+
+    let parse_days = |args: &[&str]| -> DynResult<Vec<u32>> {
+        match args {
+            [] => Ok(days.to_vec()),
+            args => (args.iter())
+                .map(|d| d.parse().map_err(|_| format!("invalid day: {}", d).into()))
+                .collect(),
+        }
+    };
+
+    let mut cache = Cache::open(INPUTS_DIR)?;
+    match positional.split_first() {
+        None | Some((&"status", _)) => {
+            let days = parse_days(positional.get(1..).unwrap_or(&[]))?;
+            let mut bad = 0;
+            for day in days {
+                let status = cache.status(day)?;
+                if status != Status::Ok {
+                    bad += 1;
+                }
+                println!("{:>3}  {:?}", day, status);
+            }
+            if bad != 0 {
+                return Err(format!("{} inputs need attention", bad).into());
+            }
+        }
+        Some((&"normalize", days)) => {
+            let mut bad = 0;
+            for day in parse_days(days)? {
+                match cache.normalize(day)? {
+                    Status::Ok | Status::Missing => {}
+                    Status::Unnormalized => println!("{:>3}  normalized", day),
+                    status => {
+                        bad += 1;
+                        println!("{:>3}  {:?}", day, status);
+                    }
+                }
+            }
+            if bad != 0 {
+                return Err(format!("{} inputs need attention", bad).into());
+            }
+        }
+        Some((&"import", [path])) => {
+            for (day, text) in import(Path::new(path))? {
+                let stored = cache.store(day, &text, force)?;
+                println!(
+                    "{:>3}  {}",
+                    day,
+                    if stored { "imported" } else { "unchanged" }
+                );
+            }
+            cache.save()?;
+        }
+        Some((&"fetch", days)) => {
+            let provider = provider(&url)?;
+            for day in parse_days(days)? {
+                if !force && cache.status(day)? != Status::Missing {
+                    continue;
+                }
+                let text = provider.fetch(day)?;
+                cache.store(day, &text, force)?;
+                println!("{:>3}  fetched", day);
+            }
+            cache.save()?;
+        }
+        _ => {
+            return Err(
+                "usage: inputs [status | normalize | fetch [<day>...] | import <dir|tar>] \
+                        [--force] [--provider <url>]"
+                    .into(),
+            )
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn tempdir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc19-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn store_and_check() {
+        let dir = tempdir("cache");
+        let mut cache = Cache::open(&dir).unwrap();
+
+        assert_eq!(cache.status(1).unwrap(), Status::Missing);
+        assert!(cache.store(1, "1,2,3\n\n ", false).unwrap());
+        assert!(!cache.store(1, "1,2,3", false).unwrap());
+        assert!(cache.store(1, "4,5,6", false).is_err());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("1.txt")).unwrap(),
+            "1,2,3\n"
+        );
+        assert_eq!(cache.status(1).unwrap(), Status::Ok);
+        cache.save().unwrap();
+
+        let mut cache = Cache::open(&dir).unwrap();
+        std::fs::write(dir.join("1.txt"), "1,2,3").unwrap();
+        assert_eq!(cache.status(1).unwrap(), Status::Ok);
+        // trailing whitespace isn't worth rewriting an input over
+        assert!(!cache.store(1, "1,2,3\n", false).unwrap());
+        assert_eq!(std::fs::read_to_string(dir.join("1.txt")).unwrap(), "1,2,3");
+        assert_eq!(cache.normalize(1).unwrap(), Status::Ok);
+        assert_eq!(std::fs::read_to_string(dir.join("1.txt")).unwrap(), "1,2,3");
+
+        std::fs::write(dir.join("1.txt"), "1,2,3\r\n\n").unwrap();
+        assert_eq!(cache.status(1).unwrap(), Status::Unnormalized);
+        assert_eq!(cache.normalize(1).unwrap(), Status::Unnormalized);
+        assert_eq!(
+            std::fs::read_to_string(dir.join("1.txt")).unwrap(),
+            "1,2,3\n"
+        );
+        assert_eq!(cache.status(1).unwrap(), Status::Ok);
+
+        // edited inputs aren't approved by normalizing them
+        std::fs::write(dir.join("1.txt"), "1,2,4\n\n").unwrap();
+        assert_eq!(cache.status(1).unwrap(), Status::Modified);
+        assert_eq!(cache.normalize(1).unwrap(), Status::Modified);
+        assert_eq!(cache.status(1).unwrap(), Status::Modified);
+        assert_eq!(
+            std::fs::read_to_string(dir.join("1.txt")).unwrap(),
+            "1,2,4\n\n"
+        );
+        std::fs::write(dir.join("2.txt"), "1\r\n").unwrap();
+        assert_eq!(cache.status(2).unwrap(), Status::Unrecorded);
+        assert_eq!(cache.normalize(2).unwrap(), Status::Unrecorded);
+        assert_eq!(cache.status(2).unwrap(), Status::Unrecorded);
+        // placeholders are replaced without needing --force
+        std::fs::write(dir.join("3.txt"), "").unwrap();
+        assert_eq!(cache.status(3).unwrap(), Status::Missing);
+        assert!(cache.store(3, "7", false).unwrap());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn tar_archive() {
+        fn entry(name: &str, contents: &[u8]) -> Vec<u8> {
+            let mut header = vec![0; 512];
+            header[..name.len()].copy_from_slice(name.as_bytes());
+            let size = format!("{:011o}\0", contents.len());
+            header[124..136].copy_from_slice(size.as_bytes());
+            header[156] = b'0';
+
+            let mut data = contents.to_vec();
+            data.resize(contents.len().div_ceil(512) * 512, 0);
+            [header, data].concat()
+        }
+
+        let tar = [
+            entry("inputs/day3.txt", b"R8,U5"),
+            entry("inputs/README", b"hello"),
+            entry("inputs/12.txt", &[b'x'; 600]),
+            vec![0; 1024],
+        ]
+        .concat();
+
+        let files = read_tar(&tar).unwrap();
+        assert_eq!(files.len(), 3);
+        assert_eq!(files[0], ("inputs/day3.txt".to_string(), b"R8,U5".to_vec()));
+        assert_eq!(files[2].1.len(), 600);
+
+        let inputs = files
+            .iter()
+            .filter_map(|(name, _)| day_from_name(name))
+            .collect::<Vec<_>>();
+        assert_eq!(inputs, vec![3, 12]);
+
+        // sizes which run past the end of the archive
+        let mut bad = entry("1.txt", b"1");
+        bad[124..136].copy_from_slice(b"77777777777\0");
+        assert!(read_tar(&bad).is_err());
+        bad[124..136].copy_from_slice(b"00000002000\0");
+        assert!(read_tar(&bad).is_err());
+    }
+
+    #[test]
+    fn curl_config_escaping() {
+        assert_eq!(
+            curl_config("session=abc"),
+            "header = \"cookie: session=abc\"\n"
+        );
+        assert_eq!(
+            curl_config(r#"a="b\c""#).trim_end(),
+            r#"header = "cookie: a=\"b\\c\"""#
+        );
+    }
+
+    #[test]
+    fn http_provider() {
+        // a stand-in for the AoC server
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            for _ in 0..2 {
+                let (mut conn, _) = listener.accept().unwrap();
+                let mut req = [0; 1024];
+                let n = conn.read(&mut req).unwrap();
+                let req = String::from_utf8_lossy(&req[..n]).into_owned();
+
+                let resp = if req.starts_with("GET /2019/day/1/input ")
+                    && req.contains("Cookie: session=abc\r\n")
+                {
+                    "HTTP/1.0 200 OK\r\n\r\n12\n14\n".to_string()
+                } else {
+                    "HTTP/1.0 404 Not Found\r\n\r\n".to_string()
+                };
+                conn.write_all(resp.as_bytes()).unwrap();
+            }
+        });
+
+        let url = format!("http://{}/2019/", addr);
+        let provider = HttpProvider::new(&url, Some("session=abc".to_string())).unwrap();
+        assert_eq!(provider.fetch(1).unwrap(), "12\n14\n");
+        assert!(provider.fetch(2).is_err());
+
+        server.join().unwrap();
+    }
+}
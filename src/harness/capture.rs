@@ -0,0 +1,71 @@
+//! Per-thread stdout capture.
+//!
+//! Solutions which draw to the terminal should write to [`stdout`] (instead of
+//! using `print!`), so that the harness can capture their output when running
+//! several solutions at once.
+
+use std::cell::RefCell;
+use std::io::{self, Write};
+
+thread_local! {
+    static CAPTURED: RefCell<Option<Vec<u8>>> = const { RefCell::new(None) };
+}
+
+/// A handle to stdout, which is redirected into a buffer while the current
+/// thread is running inside [`capture`].
+pub struct Stdout {
+    _private: (),
+}
+
+/// Returns a handle to the (possibly captured) stdout.
+pub fn stdout() -> Stdout {
+    Stdout { _private: () }
+}
+
+impl Write for Stdout {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        CAPTURED.with(|captured| match &mut *captured.borrow_mut() {
+            Some(captured) => {
+                captured.extend_from_slice(buf);
+                Ok(buf.len())
+            }
+            None => io::stdout().write(buf),
+        })
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        CAPTURED.with(|captured| match &*captured.borrow() {
+            Some(_) => Ok(()),
+            None => io::stdout().flush(),
+        })
+    }
+}
+
+/// Whether the current thread's [`stdout`] is being captured.
+pub fn is_capturing() -> bool {
+    CAPTURED.with(|captured| captured.borrow().is_some())
+}
+
+/// Run `f`, capturing anything it writes to [`stdout`].
+pub fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<u8>) {
+    let prev = CAPTURED.with(|captured| captured.replace(Some(Vec::new())));
+    let ret = f();
+    let output = CAPTURED.with(|captured| captured.replace(prev));
+    (ret, output.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested() {
+        let (((), inner), outer) = capture(|| {
+            write!(stdout(), "outer").unwrap();
+            capture(|| write!(stdout(), "inner").unwrap())
+        });
+
+        assert_eq!(inner, b"inner");
+        assert_eq!(outer, b"outer");
+    }
+}
@@ -0,0 +1,162 @@
+//! Example inputs (e.g: from the puzzle descriptions), discovered on disk.
+//!
+//! Examples live in `./examples/<day>/<name>.txt`, alongside a
+//! `<name>.answers` sidecar listing the expected answer for each question the
+//! example applies to. Each sidecar line is of the form
+//! `<question> <answer> [<args>...]`, where the args are passed to the
+//! solution (e.g: day 12's iteration count). Blank lines and lines starting
+//! with `#` are ignored.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::Report;
+use crate::DynResult;
+
+use super::input::Input;
+use super::{run_input, Solution};
+
+/// Default location of the examples directory.
+pub const EXAMPLES_DIR: &str = "./examples";
+
+/// An expected answer for a single question.
+#[derive(Debug)]
+pub struct Expected {
+    pub question: String,
+    pub answer: String,
+    pub args: Vec<String>,
+}
+
+/// An example input, and it's expected answers.
+#[derive(Debug)]
+pub struct Example {
+    pub name: String,
+    pub input: String,
+    pub expected: Vec<Expected>,
+}
+
+fn parse_sidecar(path: &Path) -> DynResult<Vec<Expected>> {
+    let file = std::fs::read_to_string(path)
+        .map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+
+    let mut expected = Vec::new();
+    for (i, ln) in file.lines().enumerate() {
+        if ln.trim().is_empty() || ln.starts_with('#') {
+            continue;
+        }
+
+        let mut words = ln.split_whitespace().map(|s| s.to_string());
+        match (words.next(), words.next()) {
+            (Some(question), Some(answer)) => expected.push(Expected {
+                question,
+                answer,
+                args: words.collect(),
+            }),
+            _ => {
+                return Err(format!(
+                    "{}:{}: expected `<question> <answer> [<args>...]`",
+                    path.display(),
+                    i + 1
+                )
+                .into())
+            }
+        }
+    }
+
+    Ok(expected)
+}
+
+/// Discover every example for a particular day, in name order. Examples
+/// without an answers sidecar are skipped.
+pub fn discover(dir: impl AsRef<Path>, day: &str) -> DynResult<Vec<Example>> {
+    let dir = dir.as_ref().join(day);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths = std::fs::read_dir(&dir)
+        .map_err(|e| format!("Could not open {}: {}", dir.display(), e))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<PathBuf>, _>>()?;
+    paths.retain(|p| p.extension().map(|e| e == "txt").unwrap_or(false));
+    paths.sort();
+
+    let mut examples = Vec::new();
+    for path in paths {
+        let sidecar = path.with_extension("answers");
+        if !sidecar.is_file() {
+            continue;
+        }
+
+        let mut input = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+        input.truncate(input.trim_end().len());
+
+        examples.push(Example {
+            name: path.file_stem().unwrap().to_string_lossy().into_owned(),
+            input,
+            expected: parse_sidecar(&sidecar)?,
+        });
+    }
+
+    Ok(examples)
+}
+
+/// Run each solution against every one of it's day's examples, printing
+/// whether they produced the expected answer. Returns an error if any of them
+/// didn't.
+pub fn test_examples(dir: impl AsRef<Path>, solutions: &[&Solution]) -> DynResult<()> {
+    let dir = dir.as_ref();
+
+    let (mut total, mut failed) = (0, 0);
+    for solution in solutions {
+        let (day, question) = (solution.day, solution.question);
+
+        for example in discover(dir, day)? {
+            let input = Input {
+                name: Some(example.name.clone()),
+                text: example.input.clone(),
+            };
+
+            let expected = example.expected.iter().filter(|e| e.question == question);
+            for expected in expected {
+                let outcome = run_input(solution, &input, &expected.args);
+
+                let (passed, status) = match &outcome.answer {
+                    Err(e) => (false, format!("ERROR: {}", Report(&**e))),
+                    Ok(actual) => match &actual.answer {
+                        Some(actual) if *actual == expected.answer => (true, "ok".to_string()),
+                        Some(actual) => (
+                            false,
+                            format!("MISMATCH\n  - {}\n  + {}", expected.answer, actual),
+                        ),
+                        None => (false, "MISMATCH (no answer)".to_string()),
+                    },
+                };
+
+                total += 1;
+                if !passed {
+                    failed += 1;
+                }
+
+                let name = match expected.args.is_empty() {
+                    true => example.name.clone(),
+                    false => format!("{} {}", example.name, expected.args.join(" ")),
+                };
+                println!(
+                    "{:>3} {:>2} {:>12.3?}  {:<16} {}",
+                    day, question, outcome.time, name, status
+                );
+            }
+        }
+    }
+
+    if total == 0 {
+        return Err("No examples found".into());
+    }
+
+    if failed != 0 {
+        return Err(format!("{} of {} examples failed", failed, total).into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,149 @@
+//! Where a solution's input comes from.
+
+use std::io::Read;
+use std::path::PathBuf;
+
+use crate::DynResult;
+
+use super::read_input;
+
+/// A source of puzzle input.
+#[derive(Debug)]
+pub enum Source {
+    /// Read from stdin.
+    Stdin,
+    /// Read from a file, or from every file in a directory.
+    Path(PathBuf),
+    /// Passed directly on the command line.
+    Inline(String),
+}
+
+/// A single puzzle input.
+#[derive(Debug)]
+pub struct Input {
+    /// Where the input came from. `None` for the default `./inputs/` file.
+    pub name: Option<String>,
+    pub text: String,
+}
+
+impl Source {
+    /// Remove any `--input <path>` (where a path of `-` means stdin) and
+    /// `--inline <input>` flags from `args`, returning the sources in the
+    /// order they were specified.
+    pub fn extract(args: &mut Vec<String>) -> DynResult<Vec<Source>> {
+        let mut sources = Vec::new();
+
+        let mut i = 0;
+        while i < args.len() {
+            let make: fn(String) -> Source = match args[i].as_str() {
+                "--input" => |path| match path.as_str() {
+                    "-" => Source::Stdin,
+                    _ => Source::Path(path.into()),
+                },
+                "--inline" => Source::Inline,
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            };
+
+            let flag = args.remove(i);
+            if i >= args.len() {
+                return Err(format!("{} requires a value", flag).into());
+            }
+            sources.push(make(args.remove(i)));
+        }
+
+        Ok(sources)
+    }
+
+    /// Load every input from the source. Directories are expanded into each
+    /// of the files they contain, in name order.
+    fn load(&self, inputs: &mut Vec<Input>) -> DynResult<()> {
+        match self {
+            Source::Stdin => {
+                let mut text = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut text)
+                    .map_err(|e| format!("Could not read stdin: {}", e))?;
+                inputs.push(Input::new("<stdin>".to_string(), text));
+            }
+            Source::Inline(text) => inputs.push(Input::new("<inline>".to_string(), text.clone())),
+            Source::Path(path) if path.is_dir() => {
+                let mut paths = std::fs::read_dir(path)
+                    .map_err(|e| format!("Could not open {}: {}", path.display(), e))?
+                    .map(|entry| entry.map(|e| e.path()))
+                    .collect::<Result<Vec<_>, _>>()?;
+                paths.retain(|p| p.is_file());
+                paths.sort();
+
+                for path in paths {
+                    Source::Path(path).load(inputs)?;
+                }
+            }
+            Source::Path(path) => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|e| format!("Could not open {}: {}", path.display(), e))?;
+                inputs.push(Input::new(path.display().to_string(), text));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Input {
+    fn new(name: String, mut text: String) -> Input {
+        text.truncate(text.trim_end().len());
+        Input {
+            name: Some(name),
+            text,
+        }
+    }
+}
+
+/// Load every input from the given sources, falling back to the day's default
+/// input file if no sources were specified.
+pub fn load(sources: &[Source], day: &str) -> DynResult<Vec<Input>> {
+    if sources.is_empty() {
+        return Ok(vec![Input {
+            name: None,
+            text: read_input(day)?,
+        }]);
+    }
+
+    let mut inputs = Vec::new();
+    for source in sources {
+        source.load(&mut inputs)?;
+    }
+
+    if inputs.is_empty() {
+        return Err("No input files found".into());
+    }
+
+    Ok(inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract() {
+        let mut args = [
+            "aoc19", "1", "--input", "-", "2", "--inline", "12", "--input", "dir",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let sources = Source::extract(&mut args).unwrap();
+
+        assert_eq!(args, vec!["aoc19", "1", "2"]);
+        assert!(matches!(sources[0], Source::Stdin));
+        assert!(matches!(&sources[1], Source::Inline(s) if s == "12"));
+        assert!(matches!(&sources[2], Source::Path(p) if p.to_str() == Some("dir")));
+
+        let mut args = vec!["--inline".to_string()];
+        assert!(Source::extract(&mut args).is_err());
+    }
+}
@@ -0,0 +1,332 @@
+//! Isolating solutions from the harness (and from each other).
+//!
+//! Panics are always caught and reported as errors. When a timeout is set,
+//! each solution is run in a worker subprocess instead, which is killed if it
+//! doesn't finish in time.
+
+use std::cell::{Cell, RefCell};
+use std::fs::OpenOptions;
+use std::io::{Read, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Once, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::capture;
+use super::json::{Json, ToJson};
+use super::{find, Rendered, Solution};
+use crate::error::Report;
+use crate::{export, vis, DynResult};
+
+/// Hidden subcommand used to run a single solution in a worker subprocess.
+pub const WORKER_MODE: &str = "__worker";
+
+/// Timeout used when running every solution (e.g: `all` or `verify`) if no
+/// `--timeout` was given, so that one solution which never finishes can't
+/// stall the whole run.
+pub const SWEEP_TIMEOUT: Duration = Duration::from_secs(60);
+
+static TIMEOUT: OnceLock<Duration> = OnceLock::new();
+
+/// Set a timeout for every subsequent solution run. Can only be set once.
+pub fn set_timeout(timeout: Duration) {
+    let _ = TIMEOUT.set(timeout);
+}
+
+/// Remove a `--timeout <secs>` flag from `args`, setting the timeout if it was
+/// present.
+pub fn extract_timeout(args: &mut Vec<String>) -> DynResult<()> {
+    let pos = match args.iter().position(|a| a == "--timeout") {
+        Some(pos) => pos,
+        None => return Ok(()),
+    };
+
+    let secs = match args.get(pos + 1).map(|s| s.parse::<f64>()) {
+        Some(Ok(secs)) if secs > 0.0 => secs,
+        _ => return Err("--timeout requires a positive number of seconds".into()),
+    };
+
+    args.drain(pos..pos + 2);
+    set_timeout(Duration::from_secs_f64(secs));
+    Ok(())
+}
+
+thread_local! {
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    static PANIC: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Install a panic hook which records (instead of printing) panics raised
+/// inside [`catch_panic`].
+fn install_hook() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        let default = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !CATCHING.with(Cell::get) {
+                return default(info);
+            }
+
+            let payload = info.payload();
+            let msg = (payload.downcast_ref::<&str>().copied())
+                .or_else(|| payload.downcast_ref::<String>().map(|s| s.as_str()))
+                .unwrap_or("Box<dyn Any>");
+            let msg = match info.location() {
+                Some(loc) => format!("panicked at {}: {}", loc, msg),
+                None => format!("panicked: {}", msg),
+            };
+            PANIC.with(|p| *p.borrow_mut() = Some(msg));
+        }));
+    });
+}
+
+/// Run `f`, reporting any panic as an error.
+pub fn catch_panic<T>(f: impl FnOnce() -> DynResult<T>) -> DynResult<T> {
+    install_hook();
+
+    let was_catching = CATCHING.with(|c| c.replace(true));
+    let res = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.with(|c| c.set(was_catching));
+
+    match res {
+        Ok(res) => res,
+        Err(_) => {
+            let msg = PANIC.with(|p| p.borrow_mut().take());
+            Err(msg.unwrap_or_else(|| "panicked".to_string()).into())
+        }
+    }
+}
+
+/// Run a solution in-process (catching any panics), timing how long it takes.
+fn run_caught(
+    solution: &Solution,
+    input: String,
+    args: &[String],
+) -> (DynResult<Rendered>, Duration) {
+    let start = Instant::now();
+    let answer = catch_panic(|| (solution.run)(input, args));
+    (answer, start.elapsed())
+}
+
+/// Run a solution, isolated according to the configured timeout.
+pub fn run(solution: &Solution, input: &str, args: &[String]) -> (DynResult<Rendered>, Duration) {
+    match TIMEOUT.get() {
+        None => run_caught(solution, input.to_string(), args),
+        Some(&timeout) => match run_in_worker(solution, input, args, timeout) {
+            Ok(res) => res,
+            Err(e) => (Err(e), Duration::default()),
+        },
+    }
+}
+
+/// Run a solution in a worker subprocess, killing it if it doesn't finish
+/// within `timeout`. The worker's stdout is forwarded to [`capture::stdout`],
+/// and it's passed the same visualization and export flags as this process.
+fn run_in_worker(
+    solution: &Solution,
+    input: &str,
+    args: &[String],
+    timeout: Duration,
+) -> DynResult<(DynResult<Rendered>, Duration)> {
+    let record = RecordFile::create()?;
+    let record_path = &record.0;
+
+    let start = Instant::now();
+    let mut child = Command::new(std::env::current_exe()?)
+        .arg(WORKER_MODE)
+        .args([solution.day, solution.question])
+        .arg(record_path)
+        .args(args)
+        .args(vis::worker_args())
+        .args(export::worker_args())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Could not spawn worker: {}", e))?;
+
+    let mut stdin = child.stdin.take().unwrap();
+    let input = input.to_string();
+    thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let mut stdout = child.stdout.take().unwrap();
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = [0; 4096];
+        while let Ok(n @ 1..) = stdout.read(&mut buf) {
+            if tx.send(buf[..n].to_vec()).is_err() {
+                break;
+            }
+        }
+    });
+
+    // forward the worker's output until it closes stdout, then wait for it to
+    // exit (killing it if it takes too long at any point)
+    let deadline = start + timeout;
+    let mut out = capture::stdout();
+    let status = loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let timed_out = match rx.recv_timeout(remaining.min(Duration::from_millis(10))) {
+            Ok(chunk) => {
+                out.write_all(&chunk)?;
+                out.flush()?;
+                false
+            }
+            Err(RecvTimeoutError::Disconnected) | Err(RecvTimeoutError::Timeout) => {
+                if let Some(status) = child.try_wait()? {
+                    // drain any remaining output
+                    for chunk in rx.try_iter() {
+                        out.write_all(&chunk)?;
+                    }
+                    break status;
+                }
+                remaining == Duration::default()
+            }
+        };
+
+        if timed_out {
+            child.kill()?;
+            child.wait()?;
+            let err = format!("timed out after {:.3?}", timeout);
+            return Ok((Err(err.into()), timeout));
+        }
+    };
+
+    match std::fs::read_to_string(record_path) {
+        Ok(record) if !record.is_empty() => parse_record(&record),
+        _ => Err(format!("worker exited unexpectedly ({})", status).into()),
+    }
+}
+
+/// The file a worker writes it's result to, which is removed once dropped.
+struct RecordFile(PathBuf);
+
+impl RecordFile {
+    /// Create a new (empty) record file in the temp dir. The file is created
+    /// up front (failing if something already exists at it's path), so that
+    /// the worker never follows a link planted there by someone else.
+    fn create() -> DynResult<RecordFile> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "aoc19-worker-{}-{}.json",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| format!("Could not create {}: {}", path.display(), e))?;
+        Ok(RecordFile(path))
+    }
+}
+
+impl Drop for RecordFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Serialize the result of a worker's solution run.
+fn write_record(answer: &DynResult<Rendered>, time: Duration) -> Json {
+    let opt = |s: &Option<String>| {
+        s.as_ref()
+            .map(|s| Json::String(s.clone()))
+            .unwrap_or(Json::Null)
+    };
+    match answer {
+        Ok(rendered) => Json::object(vec![
+            ("answer", opt(&rendered.answer)),
+            ("details", opt(&rendered.details)),
+            ("image", opt(&rendered.image)),
+            ("json", rendered.json.clone()),
+            ("time_ns", time.as_nanos().to_json()),
+        ]),
+        Err(e) => Json::object(vec![
+            ("error", Json::String(Report(&**e).to_string())),
+            ("time_ns", time.as_nanos().to_json()),
+        ]),
+    }
+}
+
+/// Deserialize the result of a worker's solution run.
+fn parse_record(record: &str) -> DynResult<(DynResult<Rendered>, Duration)> {
+    let record = Json::parse(record).map_err(|e| format!("invalid worker record: {}", e))?;
+    let time = match record.get("time_ns") {
+        Some(Json::Number(ns)) => Duration::from_nanos(ns.parse()?),
+        _ => return Err("invalid worker record: missing time".into()),
+    };
+
+    if let Some(error) = record.get("error").and_then(Json::as_str) {
+        return Ok((Err(error.into()), time));
+    }
+
+    let string = |key| record.get(key).and_then(Json::as_str).map(String::from);
+    let rendered = Rendered {
+        answer: string("answer"),
+        details: string("details"),
+        image: string("image"),
+        json: record.get("json").cloned().unwrap_or(Json::Null),
+    };
+    Ok((Ok(rendered), time))
+}
+
+/// Entry point for worker subprocesses. `args` is of the form
+/// `<day> <question> <record path> [<args>...]`, and the input is read from
+/// stdin. The result is written to the record path.
+pub fn worker(solutions: &[Solution], args: &[String]) -> DynResult<()> {
+    let (day, question, record_path) = match args {
+        [day, question, record_path, ..] => (day, question, record_path),
+        _ => return Err("invalid worker invocation".into()),
+    };
+
+    let solution = find(solutions, day, question)?;
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+
+    let (answer, time) = run_caught(solution, input, &args[3..]);
+    // the record file was already created by the parent process
+    let mut file = OpenOptions::new()
+        .write(true)
+        .truncate(true)
+        .open(record_path)?;
+    file.write_all(write_record(&answer, time).to_string().as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catches_panics() {
+        let res: DynResult<()> = catch_panic(|| panic!("oh no"));
+        let err = res.unwrap_err().to_string();
+        assert!(err.starts_with("panicked at"), "{}", err);
+        assert!(err.ends_with("oh no"), "{}", err);
+
+        assert_eq!(catch_panic(|| Ok(1)).unwrap(), 1);
+    }
+
+    #[test]
+    fn record_roundtrip() {
+        let rendered = Rendered::new((38500isize, vec![0isize, 3, 2, 4, 1]));
+        let time = Duration::from_nanos(1234);
+
+        let record = write_record(&Ok(rendered), time).to_string();
+        let (answer, parsed_time) = parse_record(&record).unwrap();
+        let answer = answer.unwrap();
+        assert_eq!(parsed_time, time);
+        assert_eq!(answer.answer.as_deref(), Some("38500"));
+        assert_eq!(answer.details.as_deref(), Some("[0, 3, 2, 4, 1]"));
+        assert_eq!(answer.json.to_string(), r#"{"0":38500,"1":[0,3,2,4,1]}"#);
+
+        let record = write_record(&Err("bad".into()), time).to_string();
+        let (answer, _) = parse_record(&record).unwrap();
+        assert_eq!(answer.unwrap_err().to_string(), "bad");
+    }
+}
@@ -0,0 +1,160 @@
+//! Run solutions concurrently on a pool of worker threads.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::capture::capture;
+use super::{print_header, print_row, run, Format, Outcome, Rendered, Solution};
+use crate::error::Report;
+use crate::DynResult;
+
+/// Configuration for `all` mode.
+pub struct Options {
+    /// Number of worker threads (solutions are run sequentially when 1)
+    pub jobs: usize,
+    /// Print each solution's captured output after it's result
+    pub show_output: bool,
+}
+
+impl Options {
+    /// Parse options out of `args`, returning any remaining positional
+    /// arguments. A job count of 0 uses every available core.
+    pub fn parse(args: &[String]) -> DynResult<(Options, Vec<String>)> {
+        let mut opts = Options {
+            jobs: 1,
+            show_output: false,
+        };
+        let mut positional = Vec::new();
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-j" | "--jobs" => {
+                    let val = args.next().ok_or(format!("{} requires a value", arg))?;
+                    opts.jobs = val.parse().map_err(|_| "invalid job count")?;
+                }
+                "--show-output" => opts.show_output = true,
+                _ => positional.push(arg.clone()),
+            }
+        }
+
+        if opts.jobs == 0 {
+            opts.jobs = thread::available_parallelism().map_or(1, |n| n.get());
+        }
+
+        Ok((opts, positional))
+    }
+}
+
+/// The result of a solution run on a worker thread.
+struct Finished {
+    answer: Result<Rendered, String>,
+    time: Duration,
+    output: Vec<u8>,
+}
+
+/// Run every solution with default arguments across a pool of worker threads,
+/// capturing anything they write to stdout. Results are printed in order as
+/// they become available, followed by the aggregate timings. Returns an error
+/// if any solution failed.
+pub fn run_all(solutions: &[&Solution], format: Format, opts: &Options) -> DynResult<()> {
+    let jobs = opts.jobs.clamp(1, solutions.len().max(1));
+
+    if format == Format::Text {
+        print_header();
+    }
+
+    let start = Instant::now();
+    let next = AtomicUsize::new(0);
+    let (tx, rx) = mpsc::channel();
+
+    let mut failed = 0;
+    let mut total = Duration::default();
+    let mut with_output = 0;
+
+    thread::scope(|s| {
+        for _ in 0..jobs {
+            let (tx, next) = (tx.clone(), &next);
+            s.spawn(move || {
+                loop {
+                    let idx = next.fetch_add(1, Ordering::Relaxed);
+                    let solution = match solutions.get(idx) {
+                        Some(solution) => solution,
+                        None => break,
+                    };
+
+                    let (outcome, output) = capture(|| run(solution, &[]));
+                    let finished = Finished {
+                        // errors aren't `Send`, so they're sent back as strings
+                        answer: outcome.answer.map_err(|e| Report(&*e).to_string()),
+                        time: outcome.time,
+                        output,
+                    };
+                    if tx.send((idx, finished)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(tx);
+
+        // print results in order, as soon as all the preceding ones are done
+        let mut done = solutions.iter().map(|_| None).collect::<Vec<_>>();
+        let mut printed = 0;
+        for (idx, finished) in rx {
+            done[idx] = Some(finished);
+
+            while let Some(Some(finished)) = done.get_mut(printed).map(Option::take) {
+                let outcome = Outcome {
+                    solution: solutions[printed],
+                    input: None,
+                    answer: finished.answer.map_err(Into::into),
+                    time: finished.time,
+                };
+
+                match format {
+                    Format::Text => print_row(&outcome),
+                    Format::Json => println!("{}", outcome.to_json()),
+                }
+
+                if !finished.output.is_empty() {
+                    with_output += 1;
+                    if opts.show_output {
+                        println!("{}", String::from_utf8_lossy(&finished.output));
+                    }
+                }
+
+                total += outcome.time;
+                if outcome.answer.is_err() {
+                    failed += 1;
+                }
+                printed += 1;
+            }
+        }
+    });
+
+    if format == Format::Text {
+        let wall = start.elapsed();
+        println!("{:>3} {:>2} {:>12.3?}", "", "", total);
+        println!(
+            "wall-clock {:.3?} on {} workers ({:.1}x speedup)",
+            wall,
+            jobs,
+            total.as_secs_f64() / wall.as_secs_f64()
+        );
+        if with_output != 0 && !opts.show_output {
+            println!(
+                "{} solutions wrote to stdout (use --show-output to see it)",
+                with_output
+            );
+        }
+    }
+
+    if failed != 0 {
+        return Err(format!("{} of {} parts failed", failed, solutions.len()).into());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+//! Scaffolding for new days.
+
+use std::path::Path;
+
+use crate::DynResult;
+
+const TEMPLATE_PATH: &str = "./src/dayX.rs";
+
+/// Create a file (and any missing parent directories), unless it already
+/// exists.
+fn create(path: impl AsRef<Path>, contents: &str) -> DynResult<()> {
+    let path = path.as_ref();
+    if path.exists() {
+        println!("exists   {}", path.display());
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents)
+        .map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+    println!("created  {}", path.display());
+    Ok(())
+}
+
+/// Create a new day's module from the `dayX.rs` template, along with
+/// placeholder input and example files. The new day is registered
+/// automatically by `build.rs`.
+///
+/// Must be run from the root of the repo.
+pub fn new_day(day: &str) -> DynResult<()> {
+    // normalize the day (e.g: `01` to `1`), so the module name matches what
+    // `build.rs` registers
+    let day = match day.parse::<u32>() {
+        Ok(day @ 1..=25) => day,
+        _ => return Err("Day must be a number between 1 and 25".into()),
+    };
+
+    let module_path = format!("./src/day{}.rs", day);
+    if Path::new(&module_path).exists() {
+        return Err(format!("{} already exists", module_path).into());
+    }
+
+    let template = std::fs::read_to_string(TEMPLATE_PATH)
+        .map_err(|e| format!("Could not open {}: {}", TEMPLATE_PATH, e))?;
+
+    create(&module_path, &template)?;
+    create(format!("./inputs/{}.txt", day), "")?;
+    create(format!("./examples/{}/e1.txt", day), "")?;
+    create(
+        format!("./examples/{}/e1.answers", day),
+        "# <question> <answer> [<args>...]\n",
+    )?;
+
+    Ok(())
+}
@@ -1,7 +1,8 @@
 #![allow(clippy::unreadable_literal)]
 #![allow(clippy::type_complexity)]
 
-/// Catch-all error type (works with anything that implements std::error::Error)
+/// Catch-all error type (works with anything that implements std::error::Error).
+/// See [`error`] for attaching context to errors.
 pub type DynResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
 
 pub mod prelude {
@@ -13,7 +14,12 @@ pub mod prelude {
     pub use itertools::Itertools;
 
     // useful AOC things
-    pub use crate::DynResult;
+    pub use crate::error::Context;
+    pub use crate::export::{self, Frames, Raster};
+    pub use crate::grid::{Bounds, Dir, Grid, Point, SparseGrid};
+    pub use crate::harness::args::{Args, Opt};
+    pub use crate::vis::Vis;
+    pub use crate::{error, ocr, parse, search, Answer, DynResult, Image};
     pub use intcode::{self, Intcode};
 
     pub trait GcdLcm {
@@ -55,6 +61,8 @@ pub mod prelude {
         use std::collections::hash_map::DefaultHasher;
         use std::hash::{Hash, Hasher};
 
+        pub use crate::harness::capture::stdout;
+
         pub fn hash<T: Hash>(t: &T) -> u64 {
             let mut s = DefaultHasher::new();
             t.hash(&mut s);
@@ -63,61 +71,130 @@ pub mod prelude {
     }
 }
 
-// Utulity macro to make adding new days a breeze
-macro_rules! days {
-    ($($day:ident),* $(,)*) => {
-        $(mod $day;)*
-
-        fn route_day(day: &str, question: &str, input: String, other_args: &[String]) -> DynResult<()> {
-            let day = format!("day{}", day);
-
-            match day.as_str() {
-                $(stringify!($day) => match question {
-                    "1" => println!("Answer: {:?}", $day::q1(input, other_args)?),
-                    "2" => println!("Answer: {:?}", $day::q2(input, other_args)?),
-                    _ => return Err("Unknown question".into()),
-                })*
-                _ => return Err("Unknown day".into()),
-            }
-
-            Ok(())
+mod answer;
+pub mod error;
+pub mod export;
+pub mod grid;
+mod harness;
+pub mod ocr;
+pub mod parse;
+pub mod search;
+pub mod vis;
+
+pub use answer::{Answer, Image};
+
+// Utility macro used by the routing table generated by `build.rs`, which
+// registers every `dayN` module in `src/`
+macro_rules! solution {
+    ($day:ident, $q:ident, $opts:expr, $slow:expr) => {
+        harness::Solution {
+            day: &stringify!($day)[3..],
+            question: &stringify!($q)[1..],
+            opts: $opts,
+            slow: $slow,
+            run: |input, args| Ok(harness::Rendered::new(days::$day::$q(input, args)?)),
         }
     };
 }
 
-days! {
-    day1,
-    day2,
-    day3,
-    day4,
-    day5,
-    day6,
-    day7,
-    day8,
-    day9,
-    day10,
-    day11,
-    day12,
-    day13,
-    day14,
-    day15,
-    day16,
+include!(concat!(env!("OUT_DIR"), "/days.rs"));
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("Error: {:#}", error::Report(&*e));
+        std::process::exit(1);
+    }
 }
 
-fn main() -> DynResult<()> {
-    let args = std::env::args().collect::<Vec<String>>();
+fn run() -> DynResult<()> {
+    let mut args = std::env::args().collect::<Vec<String>>();
+    let format = harness::Format::extract(&mut args)?;
+    let sources = harness::input::Source::extract(&mut args)?;
+    harness::isolate::extract_timeout(&mut args)?;
+    vis::extract_config(&mut args)?;
+    export::extract_config(&mut args)?;
+
+    let solutions = solutions();
+
+    let mode = args.get(1).map(|s| s.as_str());
+    if matches!(
+        mode,
+        Some("all" | "verify" | "record" | "bench" | "test-examples" | "inputs")
+    ) && !sources.is_empty()
+    {
+        return Err("--input / --inline can only be used when running a single question".into());
+    }
+
+    if matches!(mode, Some("all" | "verify" | "record")) {
+        // no-op if a --timeout was already given
+        harness::isolate::set_timeout(harness::isolate::SWEEP_TIMEOUT);
+    }
+
+    match mode {
+        Some(harness::isolate::WORKER_MODE) => {
+            return harness::isolate::worker(&solutions, &args[2..]);
+        }
+        Some("all") => {
+            let (opts, args) = harness::parallel::Options::parse(&args[2..])?;
+            let selected = harness::select(&solutions, &args)?;
+            return match opts.jobs {
+                1 => harness::run_all(&selected, format),
+                _ => harness::parallel::run_all(&selected, format, &opts),
+            };
+        }
+        Some("verify") => return harness::verify(&harness::select(&solutions, &args[2..])?),
+        Some("record") => return harness::record(&harness::select(&solutions, &args[2..])?),
+        Some("new") => {
+            let day = args.get(2).ok_or("Must specify a day (e.g: new 17)")?;
+            return harness::scaffold::new_day(day);
+        }
+        Some("inputs") => {
+            let mut days = solutions
+                .iter()
+                .filter_map(|s| s.day.parse().ok())
+                .collect::<Vec<u32>>();
+            days.dedup();
+            return harness::cache::run(&args[2..], &days);
+        }
+        Some("test-examples") => {
+            return harness::examples::test_examples(
+                harness::examples::EXAMPLES_DIR,
+                &harness::select(&solutions, &args[2..])?,
+            )
+        }
+        Some("bench") => {
+            let (opts, args) = harness::bench::Options::parse(&args[2..])?;
+            return harness::bench::bench(&harness::select(&solutions, &args)?, &opts);
+        }
+        _ => {}
+    }
+
+    if let Some(pos) = args.iter().position(|a| a == "--help") {
+        args.remove(pos);
+        let day = args.get(1).ok_or("Must specify a day (e.g: 3 --help)")?;
+        return harness::help(&solutions, day);
+    }
 
     let (day, question) = match (args.get(1), args.get(2)) {
         (None, _) | (_, None) => return Err("Must specify day and question (e.g: 3 1)".into()),
         (Some(d), Some(q)) => (d.as_str(), q.as_str()),
     };
 
-    let input_path = format!("./inputs/{}.txt", day);
-    let input_path = std::path::Path::new(&input_path);
+    let solution = harness::find(&solutions, day, question)?;
+    solution.check_args(&args[3..])?;
 
-    let mut input = std::fs::read_to_string(input_path)
-        .map_err(|e| format!("Could not open {}: {}", input_path.to_string_lossy(), e))?;
-    input.truncate(input.trim_end().len());
+    let inputs = harness::input::load(&sources, day)?;
+    harness::run_inputs(solution, &inputs, &args[3..], format)
+}
 
-    route_day(day, question, input, &args[3..])
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn examples() {
+        let solutions = solutions();
+        let solutions = solutions.iter().collect::<Vec<_>>();
+        harness::examples::test_examples(harness::examples::EXAMPLES_DIR, &solutions).unwrap();
+    }
 }
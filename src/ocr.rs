@@ -0,0 +1,175 @@
+//! Recognize the block letters some puzzles spell their answers out in.
+//!
+//! AoC uses two fonts: a 6 pixel tall one (e.g: 2019 days 8 and 11), and a
+//! 10 pixel tall one (e.g: 2018 day 10). Letters are separated by at least one
+//! blank column, and not every letter of the alphabet appears in either font.
+
+use crate::Image;
+
+const FONT_6: &[(char, &[&str])] = &[
+    ('A', &[".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', &["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', &[".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', &["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', &["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', &[".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', &["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', &["###", ".#.", ".#.", ".#.", ".#.", "###"]),
+    ('J', &["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', &["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', &["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', &[".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', &["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', &["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', &[".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', &["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', &["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+    ('Z', &["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+#[rustfmt::skip]
+const FONT_10: &[(char, &[&str])] = &[
+    ('A', &["..##..", ".#..#.", "#....#", "#....#", "#....#",
+            "######", "#....#", "#....#", "#....#", "#....#"]),
+    ('B', &["#####.", "#....#", "#....#", "#....#", "#####.",
+            "#....#", "#....#", "#....#", "#....#", "#####."]),
+    ('C', &[".####.", "#....#", "#.....", "#.....", "#.....",
+            "#.....", "#.....", "#.....", "#....#", ".####."]),
+    ('E', &["######", "#.....", "#.....", "#.....", "#####.",
+            "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('F', &["######", "#.....", "#.....", "#.....", "#####.",
+            "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('G', &[".####.", "#....#", "#.....", "#.....", "#.....",
+            "#..###", "#....#", "#....#", "#...##", ".###.#"]),
+    ('H', &["#....#", "#....#", "#....#", "#....#", "######",
+            "#....#", "#....#", "#....#", "#....#", "#....#"]),
+    ('J', &["...###", "....#.", "....#.", "....#.", "....#.",
+            "....#.", "....#.", "#...#.", "#...#.", ".###.."]),
+    ('K', &["#....#", "#...#.", "#..#..", "#.#...", "##....",
+            "##....", "#.#...", "#..#..", "#...#.", "#....#"]),
+    ('L', &["#.....", "#.....", "#.....", "#.....", "#.....",
+            "#.....", "#.....", "#.....", "#.....", "######"]),
+    ('N', &["#....#", "##...#", "##...#", "#.#..#", "#.#..#",
+            "#..#.#", "#..#.#", "#...##", "#...##", "#....#"]),
+    ('P', &["#####.", "#....#", "#....#", "#....#", "#####.",
+            "#.....", "#.....", "#.....", "#.....", "#....."]),
+    ('R', &["#####.", "#....#", "#....#", "#....#", "#####.",
+            "#..#..", "#...#.", "#...#.", "#....#", "#....#"]),
+    ('X', &["#....#", "#....#", ".#..#.", ".#..#.", "..##..",
+            "..##..", ".#..#.", ".#..#.", "#....#", "#....#"]),
+    ('Z', &["######", ".....#", ".....#", "....#.", "...#..",
+            "..#...", ".#....", "#.....", "#.....", "######"]),
+];
+
+/// A single letter, as a list of rows (with any blank columns on either side
+/// trimmed off).
+type Glyph = Vec<Vec<bool>>;
+
+fn trim(glyph: Glyph) -> Glyph {
+    let lit = |x: usize| glyph.iter().any(|row| row[x]);
+    let width = glyph.first().map(|row| row.len()).unwrap_or(0);
+
+    let start = (0..width).find(|&x| lit(x)).unwrap_or(0);
+    let end = (0..width).rfind(|&x| lit(x)).map(|x| x + 1).unwrap_or(0);
+    glyph
+        .into_iter()
+        .map(|row| row[start..end.max(start)].to_vec())
+        .collect()
+}
+
+fn lookup(font: &[(char, &[&str])], glyph: &Glyph) -> Option<char> {
+    font.iter().find_map(|(c, rows)| {
+        let rows = rows
+            .iter()
+            .map(|row| row.chars().map(|px| px == '#').collect())
+            .collect();
+        match trim(rows) == *glyph {
+            true => Some(*c),
+            false => None,
+        }
+    })
+}
+
+/// Read the letters spelled out in an image. Returns `None` if the image
+/// contains anything other than letters from one of the known fonts.
+pub fn read(image: &Image) -> Option<String> {
+    // crop off any blank rows
+    let rows = image.rows().collect::<Vec<_>>();
+    let top = rows.iter().position(|row| row.contains(&true))?;
+    let bottom = rows.iter().rposition(|row| row.contains(&true))?;
+    let rows = &rows[top..=bottom];
+
+    let font = match rows.len() {
+        6 => FONT_6,
+        10 => FONT_10,
+        _ => return None,
+    };
+
+    // split the image into letters, on blank columns
+    let mut letters = Vec::new();
+    let mut glyph: Glyph = vec![Vec::new(); rows.len()];
+    for x in 0..image.width() {
+        if rows.iter().any(|row| row[x]) {
+            for (y, row) in rows.iter().enumerate() {
+                glyph[y].push(row[x]);
+            }
+        } else if !glyph[0].is_empty() {
+            letters.push(std::mem::replace(&mut glyph, vec![Vec::new(); rows.len()]));
+        }
+    }
+    if !glyph[0].is_empty() {
+        letters.push(glyph);
+    }
+
+    letters.iter().map(|glyph| lookup(font, glyph)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(rows: &[&str]) -> Image {
+        let width = rows[0].len();
+        let pixels = rows.iter().flat_map(|row| row.chars().map(|c| c == '#'));
+        Image::new(width, pixels.collect()).unwrap()
+    }
+
+    #[test]
+    fn font_6() {
+        let img = image(&[
+            "..........................",
+            ".#..#..##..###...##..####.",
+            ".#..#.#..#.#..#.#..#.#....",
+            ".####.#....###..#....###..",
+            ".#..#.#.##.#..#.#....#....",
+            ".#..#.#..#.#..#.#..#.#....",
+            ".#..#..###.###...##..#....",
+        ]);
+        assert_eq!(read(&img).unwrap(), "HGBCF");
+
+        // every glyph should be recognizable
+        for (c, rows) in FONT_6.iter().chain(FONT_10) {
+            assert_eq!(read(&image(rows)), Some(c.to_string()));
+        }
+    }
+
+    #[test]
+    fn font_10() {
+        let (_, x) = FONT_10.iter().find(|(c, _)| *c == 'X').unwrap();
+        let (_, z) = FONT_10.iter().find(|(c, _)| *c == 'Z').unwrap();
+        let rows = x
+            .iter()
+            .zip(z.iter())
+            .map(|(x, z)| format!("{}..{}", x, z))
+            .collect::<Vec<_>>();
+        let rows = rows.iter().map(|s| s.as_str()).collect::<Vec<_>>();
+        assert_eq!(read(&image(&rows)).unwrap(), "XZ");
+    }
+
+    #[test]
+    fn unrecognized() {
+        assert_eq!(read(&image(&["...", "..."])), None);
+        assert_eq!(read(&image(&["#.#", "###", "#.#"])), None);
+        assert_eq!(read(&image(&["#", "#", "#", "#", "#", "#"])), None);
+    }
+}
@@ -0,0 +1,336 @@
+//! Helpers for parsing puzzle input.
+//!
+//! Parsers are ordinary functions over [`Span`]s: slices of the input which
+//! remember where they came from, so that errors can point at the offending
+//! line and column.
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::grid::{Grid, Point, SparseGrid};
+
+/// A parse error, and where it happened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    /// 1-indexed line number
+    pub line: usize,
+    /// 1-indexed column number (in characters)
+    pub col: usize,
+    /// The text which couldn't be parsed
+    pub text: String,
+    pub msg: String,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, col {}: {}", self.line, self.col, self.msg)?;
+        if !self.text.is_empty() {
+            // don't dump entire lines / inputs into the error
+            match self.text.char_indices().nth(40) {
+                Some((end, _)) => write!(f, " (at {:?}...)", &self.text[..end])?,
+                None => write!(f, " (at {:?})", self.text)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A slice of the input.
+#[derive(Debug, Clone, Copy)]
+pub struct Span<'a> {
+    src: &'a str,
+    start: usize,
+    end: usize,
+}
+
+impl<'a> Span<'a> {
+    /// A span covering the entire input.
+    pub fn new(src: &'a str) -> Span<'a> {
+        Span {
+            src,
+            start: 0,
+            end: src.len(),
+        }
+    }
+
+    pub fn as_str(&self) -> &'a str {
+        &self.src[self.start..self.end]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Create a span from a subslice of this span's text.
+    fn sub(&self, s: &'a str) -> Span<'a> {
+        let start = s.as_ptr() as usize - self.src.as_ptr() as usize;
+        debug_assert!(self.start <= start && start + s.len() <= self.end);
+        Span {
+            src: self.src,
+            start,
+            end: start + s.len(),
+        }
+    }
+
+    /// Create an error pointing at this span.
+    pub fn error(&self, msg: impl Into<String>) -> Error {
+        let before = &self.src[..self.start];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        Error {
+            line: before.matches('\n').count() + 1,
+            col: before[line_start..].chars().count() + 1,
+            text: self.as_str().to_string(),
+            msg: msg.into(),
+        }
+    }
+
+    /// Parse the (trimmed) span using it's `FromStr` implementation.
+    pub fn parse<T>(&self) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        let s = self.trim();
+        s.as_str().parse::<T>().map_err(|e| s.error(e.to_string()))
+    }
+
+    pub fn trim(&self) -> Span<'a> {
+        self.sub(self.as_str().trim())
+    }
+
+    pub fn lines(self) -> impl Iterator<Item = Span<'a>> + 'a {
+        self.as_str().lines().map(move |ln| self.sub(ln))
+    }
+
+    pub fn split(self, sep: &'a str) -> impl Iterator<Item = Span<'a>> + 'a {
+        self.as_str().split(sep).map(move |s| self.sub(s))
+    }
+
+    /// Split the span in two around the first occurrence of `sep`.
+    pub fn split_once(&self, sep: &str) -> Result<(Span<'a>, Span<'a>)> {
+        match self.as_str().split_once(sep) {
+            Some((a, b)) => Ok((self.sub(a), self.sub(b))),
+            None => Err(self.error(format!("expected {:?}", sep))),
+        }
+    }
+
+    /// Split the span in two after the `n`th character.
+    pub fn split_at(&self, n: usize) -> Result<(Span<'a>, Span<'a>)> {
+        let s = self.as_str();
+        match s.char_indices().map(|(i, _)| i).chain(Some(s.len())).nth(n) {
+            Some(i) => Ok((self.sub(&s[..i]), self.sub(&s[i..]))),
+            None => Err(self.error(format!("expected at least {} characters", n))),
+        }
+    }
+
+    pub fn strip_prefix(&self, prefix: &str) -> Result<Span<'a>> {
+        match self.as_str().strip_prefix(prefix) {
+            Some(s) => Ok(self.sub(s)),
+            None => Err(self.error(format!("expected {:?}", prefix))),
+        }
+    }
+
+    pub fn strip_suffix(&self, suffix: &str) -> Result<Span<'a>> {
+        match self.as_str().strip_suffix(suffix) {
+            Some(s) => Ok(self.sub(s)),
+            None => Err(self.error(format!("expected {:?}", suffix))),
+        }
+    }
+
+    /// Each character, as a span.
+    pub fn chars(self) -> impl Iterator<Item = (char, Span<'a>)> + 'a {
+        let s = self.as_str();
+        s.char_indices()
+            .map(move |(i, c)| (c, self.sub(&s[i..i + c.len_utf8()])))
+    }
+}
+
+impl Display for Span<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Parse each line of the input.
+pub fn lines<'a, T>(input: &'a str, f: impl FnMut(Span<'a>) -> Result<T>) -> Result<Vec<T>> {
+    Span::new(input).lines().map(f).collect()
+}
+
+/// Parse a `sep` separated list of values (e.g: `1,2,3`).
+pub fn list<'a, T>(span: Span<'a>, sep: &'a str) -> Result<Vec<T>>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    span.split(sep).map(|s| s.parse()).collect()
+}
+
+/// Parse a string of single digit numbers (e.g: `12345`).
+pub fn digits(span: Span) -> Result<Vec<u32>> {
+    span.trim()
+        .chars()
+        .map(|(c, s)| c.to_digit(10).ok_or_else(|| s.error("expected a digit")))
+        .collect()
+}
+
+/// Parse a `N NAME` quantity (e.g: `7 ORE`).
+pub fn quantity<'a, T>(span: Span<'a>) -> Result<(T, &'a str)>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let (n, name) = span.trim().split_once(" ")?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(name.error("expected a name"));
+    }
+    Ok((n.parse()?, name.as_str()))
+}
+
+/// A list of `key=value` fields (e.g: `x=-1, y=0, z=2`).
+pub struct Record<'a> {
+    span: Span<'a>,
+    fields: Vec<(&'a str, Span<'a>)>,
+}
+
+impl<'a> Record<'a> {
+    /// Parse the value of a field.
+    pub fn get<T>(&self, key: &str) -> Result<T>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        match self.fields.iter().find(|(k, _)| *k == key) {
+            Some((_, val)) => val.parse(),
+            None => Err(self.span.error(format!("missing field `{}`", key))),
+        }
+    }
+}
+
+/// Parse a `sep` separated list of `key=value` fields.
+pub fn record<'a>(span: Span<'a>, sep: &'a str) -> Result<Record<'a>> {
+    let fields = span
+        .split(sep)
+        .map(|field| {
+            let (key, val) = field.split_once("=")?;
+            Ok((key.trim().as_str(), val))
+        })
+        .collect::<Result<_>>()?;
+    Ok(Record { span, fields })
+}
+
+/// What a grid's `cell` callback returns: the parsed cell, or a message
+/// explaining why the character is invalid (reported at it's position).
+pub type CellResult<T> = std::result::Result<T, String>;
+
+/// Parse a grid of characters, where `cell` returns an error message for
+/// invalid characters. Every line must be the same length.
+pub fn grid<T>(input: &str, mut cell: impl FnMut(char) -> CellResult<T>) -> Result<Grid<T>> {
+    let mut width = None;
+    let mut height = 0;
+    let mut cells = Vec::new();
+
+    for ln in Span::new(input).lines() {
+        let len = cells.len();
+        for (c, s) in ln.chars() {
+            cells.push(cell(c).map_err(|msg| s.error(msg))?);
+        }
+
+        let len = cells.len() - len;
+        if *width.get_or_insert(len) != len {
+            return Err(ln.error("line isn't the same length as the first"));
+        }
+        height += 1;
+    }
+
+    Ok(Grid::from_cells(width.unwrap_or(0), height, cells))
+}
+
+/// Parse a sparse grid of characters, where `cell` returns `Ok(None)` for
+/// empty cells, or an error message for invalid characters.
+pub fn sparse_grid<T>(
+    input: &str,
+    mut cell: impl FnMut(char) -> CellResult<Option<T>>,
+) -> Result<SparseGrid<T>> {
+    let mut grid = SparseGrid::new();
+    for (y, ln) in Span::new(input).lines().enumerate() {
+        for (x, (c, s)) in ln.chars().enumerate() {
+            if let Some(val) = cell(c).map_err(|msg| s.error(msg))? {
+                grid.insert(Point::new(x as i32, y as i32), val);
+            }
+        }
+    }
+    Ok(grid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors() {
+        let input = "1,2,3\n4,x,6";
+        let err = lines(input, |ln| list::<u32>(ln, ",")).unwrap_err();
+        assert_eq!((err.line, err.col, err.text.as_str()), (2, 3, "x"));
+        assert_eq!(
+            err.to_string(),
+            "line 2, col 3: invalid digit found in string (at \"x\")"
+        );
+
+        let err = digits(Span::new("12a4")).unwrap_err();
+        assert_eq!((err.line, err.col), (1, 3));
+
+        let err = Span::new("abc").split_once("=").unwrap_err();
+        assert_eq!(err.msg, "expected \"=\"");
+    }
+
+    #[test]
+    fn helpers() {
+        let parsed = lines("1,2\n3, 4", |ln| list::<i32>(ln, ",")).unwrap();
+        assert_eq!(parsed, vec![vec![1, 2], vec![3, 4]]);
+
+        assert_eq!(digits(Span::new("0123\n")).unwrap(), vec![0, 1, 2, 3]);
+
+        let (n, name) = quantity::<usize>(Span::new(" 7 ORE")).unwrap();
+        assert_eq!((n, name), (7, "ORE"));
+        assert!(quantity::<usize>(Span::new("7")).is_err());
+
+        let span = Span::new("<x=-1, y=2, z=3>");
+        let rec = record(
+            span.strip_prefix("<").unwrap().strip_suffix(">").unwrap(),
+            ",",
+        )
+        .unwrap();
+        assert_eq!(rec.get::<i32>("x").unwrap(), -1);
+        assert_eq!(rec.get::<i32>("z").unwrap(), 3);
+        let err = rec.get::<i32>("w").unwrap_err();
+        assert_eq!((err.col, err.msg.as_str()), (2, "missing field `w`"));
+
+        let (dir, dist) = Span::new("R75").split_at(1).unwrap();
+        assert_eq!((dir.as_str(), dist.parse::<u32>().unwrap()), ("R", 75));
+    }
+
+    #[test]
+    fn grids() {
+        let g = grid("#.\n.#", |c| match c {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err(format!("invalid character {:?}", c)),
+        })
+        .unwrap();
+        assert_eq!((g.width(), g.height()), (2, 2));
+
+        let err = grid("..\n.?", |c| match c {
+            '.' => Ok(c),
+            _ => Err("bad".to_string()),
+        })
+        .unwrap_err();
+        assert_eq!((err.line, err.col, err.msg.as_str()), (2, 2, "bad"));
+        let err = grid("..\n.", Ok).unwrap_err();
+        assert_eq!((err.line, err.col), (2, 1));
+    }
+}
@@ -0,0 +1,332 @@
+//! Generic graph searches.
+//!
+//! Graphs are never built up-front: instead, each search is handed a starting
+//! node and a function which returns a node's neighbors, so the same searches
+//! work on grids, maps of names, or whatever else a day's puzzle looks like.
+//!
+//! See `intcode::search` for searching over the states of an intcode program.
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::Add;
+
+use crate::DynResult;
+
+/// Walk back through the parents of `goal` to build the path that led there.
+fn reconstruct<N: Clone + Hash + Eq>(parents: &HashMap<N, N>, goal: N) -> Vec<N> {
+    let mut path = vec![goal];
+    while let Some(parent) = parents.get(path.last().unwrap()) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+/// Find the path with the fewest steps from `start` to a node satisfying
+/// `is_goal`. The returned path includes both `start` and the goal.
+pub fn bfs<N, I>(
+    start: N,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Vec<N>>
+where
+    N: Clone + Hash + Eq,
+    I: IntoIterator<Item = N>,
+{
+    let mut parents = HashMap::new();
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::new();
+
+    visited.insert(start.clone());
+    queue.push_back(start);
+
+    while let Some(node) = queue.pop_front() {
+        if is_goal(&node) {
+            return Some(reconstruct(&parents, node));
+        }
+
+        for next in neighbors(&node) {
+            if visited.insert(next.clone()) {
+                parents.insert(next.clone(), node.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+/// Find the number of steps to every node reachable from `start`.
+pub fn flood_fill<N, I>(start: N, mut neighbors: impl FnMut(&N) -> I) -> HashMap<N, usize>
+where
+    N: Clone + Hash + Eq,
+    I: IntoIterator<Item = N>,
+{
+    let mut dists = HashMap::new();
+    let mut queue = VecDeque::new();
+
+    dists.insert(start.clone(), 0);
+    queue.push_back((start, 0));
+
+    while let Some((node, dist)) = queue.pop_front() {
+        for next in neighbors(&node) {
+            if !dists.contains_key(&next) {
+                dists.insert(next.clone(), dist + 1);
+                queue.push_back((next, dist + 1));
+            }
+        }
+    }
+
+    dists
+}
+
+/// An entry in the A* priority queue, ordered such that the lowest estimated
+/// total cost is popped from the (max) heap first.
+struct Entry<N, C> {
+    estimate: C,
+    cost: C,
+    node: N,
+}
+
+impl<N, C: Ord> PartialEq for Entry<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<N, C: Ord> Eq for Entry<N, C> {}
+
+impl<N, C: Ord> PartialOrd for Entry<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> Ord for Entry<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.estimate.cmp(&self.estimate))
+            // break ties in favor of nodes closer to the goal
+            .then_with(|| self.cost.cmp(&other.cost))
+    }
+}
+
+/// Find the cheapest path from `start` to a node satisfying `is_goal`, where
+/// `neighbors` returns each neighbor along with the cost of moving to it.
+/// Returns the path (including `start` and the goal), and it's total cost.
+///
+/// `heuristic` estimates the remaining cost to reach a goal. As long as it
+/// never overestimates, the returned path is the cheapest one.
+pub fn astar<N, C, I>(
+    start: N,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<(Vec<N>, C)>
+where
+    N: Clone + Hash + Eq,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut costs = HashMap::new();
+    let mut parents = HashMap::new();
+    let mut heap = BinaryHeap::new();
+
+    costs.insert(start.clone(), C::default());
+    heap.push(Entry {
+        estimate: heuristic(&start),
+        cost: C::default(),
+        node: start,
+    });
+
+    while let Some(Entry { cost, node, .. }) = heap.pop() {
+        // skip stale entries, which have since been reached more cheaply
+        if costs.get(&node).map(|&c| cost > c).unwrap_or(false) {
+            continue;
+        }
+
+        if is_goal(&node) {
+            return Some((reconstruct(&parents, node), cost));
+        }
+
+        for (next, step) in neighbors(&node) {
+            let next_cost = cost + step;
+            if costs.get(&next).map(|&c| next_cost < c).unwrap_or(true) {
+                costs.insert(next.clone(), next_cost);
+                parents.insert(next.clone(), node.clone());
+                heap.push(Entry {
+                    estimate: next_cost + heuristic(&next),
+                    cost: next_cost,
+                    node: next,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Find the cheapest path from `start` to a node satisfying `is_goal`. See
+/// [`astar`].
+pub fn dijkstra<N, C, I>(
+    start: N,
+    neighbors: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<(Vec<N>, C)>
+where
+    N: Clone + Hash + Eq,
+    C: Copy + Ord + Default + Add<Output = C>,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar(start, neighbors, |_| C::default(), is_goal)
+}
+
+/// Sort every node reachable from `roots` such that each node comes before
+/// all the nodes it has edges to. Returns an error if the graph has a cycle.
+pub fn toposort<N, I>(
+    roots: impl IntoIterator<Item = N>,
+    mut edges: impl FnMut(&N) -> I,
+) -> DynResult<Vec<N>>
+where
+    N: Clone + Hash + Eq + Debug,
+    I: IntoIterator<Item = N>,
+{
+    // discover the graph, numbering nodes in the order they're found
+    let mut ids = HashMap::new();
+    let mut nodes = Vec::new();
+    let mut adjacent: Vec<Vec<usize>> = Vec::new();
+    let mut in_degree = Vec::new();
+
+    let mut id = |node: N, nodes: &mut Vec<N>, in_degree: &mut Vec<usize>| {
+        *ids.entry(node.clone()).or_insert_with(|| {
+            nodes.push(node);
+            in_degree.push(0);
+            nodes.len() - 1
+        })
+    };
+
+    for root in roots {
+        id(root, &mut nodes, &mut in_degree);
+    }
+
+    let mut i = 0;
+    while i < nodes.len() {
+        let mut adj = Vec::new();
+        for next in edges(&nodes[i]) {
+            let next = id(next, &mut nodes, &mut in_degree);
+            in_degree[next] += 1;
+            adj.push(next);
+        }
+        adjacent.push(adj);
+        i += 1;
+    }
+
+    // Kahn's algorithm
+    let mut queue = (0..nodes.len())
+        .filter(|&i| in_degree[i] == 0)
+        .collect::<VecDeque<_>>();
+    let mut order = Vec::new();
+
+    while let Some(i) = queue.pop_front() {
+        order.push(i);
+        for &next in &adjacent[i] {
+            in_degree[next] -= 1;
+            if in_degree[next] == 0 {
+                queue.push_back(next);
+            }
+        }
+    }
+
+    if order.len() != nodes.len() {
+        let cyclic = (0..nodes.len()).find(|&i| in_degree[i] != 0).unwrap();
+        return Err(format!("cycle detected involving {:?}", nodes[cyclic]).into());
+    }
+
+    Ok(order.into_iter().map(|i| nodes[i].clone()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::grid::{Grid, Point};
+
+    const MAZE: &str = "
+#########
+#S..#...#
+#.#.#.#.#
+#.#...#E#
+#########";
+
+    fn maze() -> (Grid<char>, Point, Point) {
+        let grid = Grid::parse(MAZE.trim(), Ok).unwrap();
+        let find = |c| grid.iter().find(|&(_, &x)| x == c).unwrap().0;
+        let (start, end) = (find('S'), find('E'));
+        (grid, start, end)
+    }
+
+    #[test]
+    fn unweighted() {
+        let (grid, start, end) = maze();
+        let open = |p: &Point| {
+            let grid = &grid;
+            p.neighbors().filter(move |&n| grid.get(n) != Some(&'#'))
+        };
+
+        let path = bfs(start, open, |&p| p == end).unwrap();
+        assert_eq!(path.len() - 1, 12);
+        assert_eq!((path[0], path[path.len() - 1]), (start, end));
+        assert!(path.windows(2).all(|w| (w[1] - w[0]).manhattan() == 1));
+
+        let dists = flood_fill(start, open);
+        assert_eq!(dists[&end], 12);
+        assert_eq!(dists.len(), 15);
+
+        assert_eq!(bfs(start, open, |_| false), None);
+    }
+
+    #[test]
+    fn weighted() {
+        let (grid, start, end) = maze();
+        // moving right is cheap
+        let open = |p: &Point| {
+            let p = *p;
+            let grid = &grid;
+            p.neighbors()
+                .filter(move |&n| grid.get(n) != Some(&'#'))
+                .map(move |n| (n, if n.x > p.x { 1 } else { 3 }))
+        };
+
+        let (path, cost) = dijkstra(start, open, |&p| p == end).unwrap();
+        assert_eq!(cost, 6 + 6 * 3);
+        assert_eq!(path.len(), 13);
+
+        let manhattan = |p: &Point| (end - *p).manhattan();
+        let (_, cost) = astar(start, open, manhattan, |&p| p == end).unwrap();
+        assert_eq!(cost, 24);
+    }
+
+    #[test]
+    fn topological() {
+        let deps: HashMap<&str, Vec<&str>> = vec![
+            ("shirt", vec!["tie", "belt"]),
+            ("tie", vec!["jacket"]),
+            ("pants", vec!["shoes", "belt"]),
+            ("belt", vec!["jacket"]),
+        ]
+        .into_iter()
+        .collect();
+        let edges = |n: &&str| deps.get(n).cloned().unwrap_or_default();
+
+        let order = toposort(vec!["shirt", "pants"], edges).unwrap();
+        let pos = |n: &str| order.iter().position(|&x| x == n).unwrap();
+        assert_eq!(order.len(), 6);
+        for (a, bs) in &deps {
+            assert!(bs.iter().all(|b| pos(a) < pos(b)));
+        }
+
+        let cyclic = |n: &u32| vec![(n + 1) % 3];
+        assert!(toposort(vec![0], cyclic).is_err());
+    }
+}
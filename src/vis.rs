@@ -0,0 +1,461 @@
+//! Terminal visualizations.
+//!
+//! Days which animate their progress draw to a [`Vis`], which forwards each
+//! frame to a [`Backend`]. Which backend is used is picked by the `--vis` and
+//! `--fps` flags (see [`extract_config`]). By default, frames are drawn to the
+//! terminal, but only if stdout actually _is_ a terminal.
+
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::harness::capture;
+use crate::harness::json::Json;
+use crate::DynResult;
+
+/// Frame rate assumed by recordings when no `--fps` cap was set.
+const DEFAULT_RECORDING_FPS: u32 = 30;
+
+/// Where frames are drawn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mode {
+    /// Draw to the terminal if stdout is a terminal, otherwise do nothing
+    Auto,
+    /// Never draw anything
+    Off,
+    /// Always draw to stdout using ANSI escapes
+    Ansi,
+    /// Record frames to an asciicast file
+    Record(PathBuf),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    pub mode: Mode,
+    /// Maximum frames per second (uncapped if `None`)
+    pub fps: Option<u32>,
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Remove any `--vis <auto|off|ansi|FILE.cast>` and `--fps <n>` flags from
+/// `args`, configuring every subsequently created [`Vis`]. Can only be set
+/// once.
+pub fn extract_config(args: &mut Vec<String>) -> DynResult<()> {
+    let _ = CONFIG.set(parse_config(args)?);
+    Ok(())
+}
+
+fn config() -> Config {
+    CONFIG.get().cloned().unwrap_or(Config {
+        mode: Mode::Auto,
+        fps: None,
+    })
+}
+
+/// Decide whether `Auto` should actually draw anything.
+fn resolve(mode: Mode) -> Mode {
+    match mode {
+        // output captured by the harness isn't going to a terminal either
+        Mode::Auto if io::stdout().is_terminal() && !capture::is_capturing() => Mode::Ansi,
+        Mode::Auto => Mode::Off,
+        mode => mode,
+    }
+}
+
+/// Flags which reproduce the current configuration in a worker subprocess.
+/// `auto` is resolved up front, since a worker's stdout is never a terminal.
+pub(crate) fn worker_args() -> Vec<String> {
+    let config = config();
+    let mode = match resolve(config.mode) {
+        Mode::Auto | Mode::Off => "off".to_string(),
+        Mode::Ansi => "ansi".to_string(),
+        Mode::Record(path) => path.display().to_string(),
+    };
+
+    let mut args = vec!["--vis".to_string(), mode];
+    if let Some(fps) = config.fps {
+        args.extend(vec!["--fps".to_string(), fps.to_string()]);
+    }
+    args
+}
+
+fn parse_config(args: &mut Vec<String>) -> DynResult<Config> {
+    let mut config = Config {
+        mode: Mode::Auto,
+        fps: None,
+    };
+
+    if let Some(pos) = args.iter().position(|a| a == "--vis") {
+        config.mode = match args.get(pos + 1).map(|s| s.as_str()) {
+            Some("auto") => Mode::Auto,
+            Some("off") => Mode::Off,
+            Some("ansi") => Mode::Ansi,
+            Some(path) if path.ends_with(".cast") => Mode::Record(path.into()),
+            Some(other) => return Err(format!("Unknown visualization mode: {}", other).into()),
+            None => return Err("--vis requires a value (auto, off, ansi, or FILE.cast)".into()),
+        };
+        args.drain(pos..pos + 2);
+    }
+
+    if let Some(pos) = args.iter().position(|a| a == "--fps") {
+        config.fps = match args.get(pos + 1).map(|s| s.parse::<u32>()) {
+            Some(Ok(fps)) if fps > 0 => Some(fps),
+            _ => return Err("--fps requires a positive number".into()),
+        };
+        args.drain(pos..pos + 2);
+    }
+
+    Ok(config)
+}
+
+/// Something frames can be drawn to.
+pub trait Backend {
+    /// Clear the screen.
+    fn clear(&mut self) -> io::Result<()>;
+
+    /// Draw `text` starting at column `x` of row `y` (both 0-indexed).
+    fn draw(&mut self, x: usize, y: usize, text: &str) -> io::Result<()>;
+
+    /// Mark the end of a frame.
+    fn present(&mut self) -> io::Result<()>;
+
+    /// Mark the end of the visualization.
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+/// Discards everything.
+pub struct NoOp;
+
+impl Backend for NoOp {
+    fn clear(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn draw(&mut self, _x: usize, _y: usize, _text: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn present(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The number of columns `text` takes up, ignoring any ANSI escapes.
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // skip to the end of the escape sequence
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// Draws to a terminal using ANSI escapes, sleeping between frames to stay
+/// under a frame-rate cap.
+pub struct Ansi<W: Write> {
+    out: W,
+    interval: Option<Duration>,
+    last_frame: Option<Instant>,
+    /// Number of columns / rows drawn to so far
+    size: (usize, usize),
+}
+
+/// The time between frames at a given frame-rate.
+fn frame_interval(fps: u32) -> Duration {
+    Duration::from_secs(1) / fps
+}
+
+impl<W: Write> Ansi<W> {
+    pub fn new(out: W, fps: Option<u32>) -> Ansi<W> {
+        Ansi::with_interval(out, fps.map(frame_interval))
+    }
+
+    fn with_interval(out: W, interval: Option<Duration>) -> Ansi<W> {
+        Ansi {
+            out,
+            interval,
+            last_frame: None,
+            size: (0, 0),
+        }
+    }
+}
+
+impl<W: Write> Backend for Ansi<W> {
+    fn clear(&mut self) -> io::Result<()> {
+        write!(self.out, "\x1b[2J\x1b[H")
+    }
+
+    fn draw(&mut self, x: usize, y: usize, text: &str) -> io::Result<()> {
+        self.size.0 = self.size.0.max(x + visible_width(text));
+        self.size.1 = self.size.1.max(y + 1);
+        write!(self.out, "\x1b[{};{}H{}", y + 1, x + 1, text)
+    }
+
+    fn present(&mut self) -> io::Result<()> {
+        if let (Some(interval), Some(last)) = (self.interval, self.last_frame) {
+            if let Some(remaining) = interval.checked_sub(last.elapsed()) {
+                thread::sleep(remaining);
+            }
+        }
+        self.last_frame = Some(Instant::now());
+        self.out.flush()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        // leave the cursor below the visualization
+        if self.size.1 != 0 {
+            write!(self.out, "\x1b[{};1H", self.size.1 + 1)?;
+        }
+        self.out.flush()
+    }
+}
+
+/// Records frames to an [asciicast v2](https://docs.asciinema.org/manual/asciicast/v2/)
+/// file, which is written once the visualization finishes.
+pub struct Asciicast {
+    path: PathBuf,
+    interval: Duration,
+    ansi: Ansi<Vec<u8>>,
+    /// (timestamp, output) of each frame
+    frames: Vec<(Duration, String)>,
+}
+
+impl Asciicast {
+    pub fn new(path: impl Into<PathBuf>, fps: u32) -> Asciicast {
+        Asciicast::with_interval(path, frame_interval(fps))
+    }
+
+    fn with_interval(path: impl Into<PathBuf>, interval: Duration) -> Asciicast {
+        Asciicast {
+            path: path.into(),
+            interval,
+            ansi: Ansi::new(Vec::new(), None),
+            frames: Vec::new(),
+        }
+    }
+
+    fn end_frame(&mut self) {
+        let output = std::mem::take(&mut self.ansi.out);
+        if !output.is_empty() {
+            // frames are timestamped at a steady rate, no matter how long
+            // they took to compute
+            let time = self.interval * self.frames.len() as u32;
+            self.frames
+                .push((time, String::from_utf8_lossy(&output).into_owned()));
+        }
+    }
+}
+
+impl Backend for Asciicast {
+    fn clear(&mut self) -> io::Result<()> {
+        self.ansi.clear()
+    }
+
+    fn draw(&mut self, x: usize, y: usize, text: &str) -> io::Result<()> {
+        self.ansi.draw(x, y, text)
+    }
+
+    fn present(&mut self) -> io::Result<()> {
+        self.end_frame();
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.ansi.finish()?;
+        self.end_frame();
+
+        let header = Json::object(vec![
+            ("version", Json::Number("2".to_string())),
+            ("width", Json::Number(self.ansi.size.0.max(1).to_string())),
+            ("height", Json::Number((self.ansi.size.1 + 1).to_string())),
+        ]);
+
+        let mut file = io::BufWriter::new(std::fs::File::create(&self.path)?);
+        writeln!(file, "{}", header)?;
+        for (time, output) in &self.frames {
+            let event = Json::Array(vec![
+                Json::Number(format!("{:.6}", time.as_secs_f64())),
+                Json::String("o".to_string()),
+                Json::String(output.clone()),
+            ]);
+            writeln!(file, "{}", event)?;
+        }
+        file.flush()
+    }
+}
+
+/// A visualization, which days draw frames to.
+pub struct Vis {
+    backend: Box<dyn Backend>,
+    enabled: bool,
+    finished: bool,
+}
+
+impl Vis {
+    /// Create a visualization using the backend selected on the command line.
+    pub fn new() -> Vis {
+        Vis::from_config(config(), None)
+    }
+
+    /// Like [`Vis::new`], but presenting frames (at most) every `interval`,
+    /// instead of at the `--fps` given on the command line.
+    pub fn with_frame_interval(interval: Duration) -> Vis {
+        Vis::from_config(config(), Some(interval))
+    }
+
+    fn from_config(config: Config, interval: Option<Duration>) -> Vis {
+        let interval = interval.or_else(|| config.fps.map(frame_interval));
+        match resolve(config.mode) {
+            Mode::Auto | Mode::Off => Vis::off(),
+            Mode::Ansi => {
+                Vis::with_backend(Box::new(Ansi::with_interval(capture::stdout(), interval)))
+            }
+            Mode::Record(path) => Vis::with_backend(Box::new(Asciicast::with_interval(
+                path,
+                interval.unwrap_or_else(|| frame_interval(DEFAULT_RECORDING_FPS)),
+            ))),
+        }
+    }
+
+    /// Create a visualization which doesn't draw anything.
+    pub fn off() -> Vis {
+        Vis {
+            backend: Box::new(NoOp),
+            enabled: false,
+            finished: false,
+        }
+    }
+
+    pub fn with_backend(backend: Box<dyn Backend>) -> Vis {
+        Vis {
+            backend,
+            enabled: true,
+            finished: false,
+        }
+    }
+
+    /// Whether anything will actually be drawn. Useful to skip rendering
+    /// frames which would just be thrown away.
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Replace the screen's contents with a new frame.
+    pub fn frame(&mut self, frame: &str) -> io::Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        self.backend.clear()?;
+        for (y, ln) in frame.lines().enumerate() {
+            self.backend.draw(0, y, ln)?;
+        }
+        self.backend.present()
+    }
+
+    /// Clear the screen, without ending the frame.
+    pub fn clear(&mut self) -> io::Result<()> {
+        self.backend.clear()
+    }
+
+    /// Update part of the screen, without ending the frame.
+    pub fn draw(&mut self, x: usize, y: usize, text: &str) -> io::Result<()> {
+        self.backend.draw(x, y, text)
+    }
+
+    /// End the current frame.
+    pub fn present(&mut self) -> io::Result<()> {
+        self.backend.present()
+    }
+
+    /// End the visualization. Called automatically when the visualization is
+    /// dropped, though any errors are ignored.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.finished = true;
+        self.backend.finish()
+    }
+}
+
+impl Default for Vis {
+    fn default() -> Vis {
+        Vis::new()
+    }
+}
+
+impl Drop for Vis {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.backend.finish();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config() {
+        let mut args = ["13", "2", "--vis", "out.cast", "--fps", "10"]
+            .map(String::from)
+            .to_vec();
+        let config = parse_config(&mut args).unwrap();
+        assert_eq!(args, ["13", "2"]);
+        assert_eq!(config.mode, Mode::Record("out.cast".into()));
+        assert_eq!(config.fps, Some(10));
+
+        let mut args = ["--vis", "sideways"].map(String::from).to_vec();
+        assert!(parse_config(&mut args).is_err());
+    }
+
+    #[test]
+    fn ansi() {
+        let mut ansi = Ansi::new(Vec::new(), Some(1000));
+        ansi.clear().unwrap();
+        ansi.draw(2, 1, "\x1b[0;41m#\x1b[0m").unwrap();
+        ansi.present().unwrap();
+        ansi.finish().unwrap();
+
+        assert_eq!(ansi.size, (3, 2));
+        assert_eq!(
+            String::from_utf8(ansi.out).unwrap(),
+            "\x1b[2J\x1b[H\x1b[2;3H\x1b[0;41m#\x1b[0m\x1b[3;1H"
+        );
+    }
+
+    #[test]
+    fn asciicast() {
+        let path = std::env::temp_dir().join(format!("aoc19-vis-{}.cast", std::process::id()));
+
+        let mut vis = Vis::with_backend(Box::new(Asciicast::new(&path, 10)));
+        vis.frame("ab\ncd").unwrap();
+        vis.draw(1, 0, "X").unwrap();
+        vis.present().unwrap();
+        vis.finish().unwrap();
+
+        let file = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let lines = file
+            .lines()
+            .map(|ln| Json::parse(ln).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0].get("width").unwrap().to_string(), "2");
+        assert_eq!(lines[2].to_string(), r#"[0.100000,"o","\u001b[1;2HX"]"#);
+        // the cursor is moved below the drawing at the end
+        assert_eq!(lines[3].to_string(), r#"[0.200000,"o","\u001b[3;1H"]"#);
+    }
+}
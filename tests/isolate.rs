@@ -0,0 +1,29 @@
+//! Runs the `aoc19` binary end-to-end, to check flags make it through to
+//! solutions running in worker subprocesses.
+
+use std::fs;
+use std::process::Command;
+
+#[test]
+fn worker_exports() {
+    let dir = std::env::temp_dir().join(format!("aoc19-worker-export-{}", std::process::id()));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_aoc19"))
+        .args([
+            "8",
+            "2",
+            "--timeout",
+            "60",
+            "--export-format",
+            "ppm",
+            "--export",
+        ])
+        .arg(&dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let image = fs::read(dir.join("8-2.ppm")).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(image.starts_with(b"P6\n"));
+}